@@ -2,18 +2,27 @@ use std::{
     collections::{BTreeMap, HashMap},
     fmt::Display,
     hash::Hash,
+    rc::Rc,
 };
 
-use crate::eval::{
-    env::Environment,
-    eval_block, eval_function_block,
-    object::{CustomHash, HashKey, Object},
+use crate::{
+    eval::{
+        env::{Environment, Frame},
+        error::ErrorKind,
+        eval_block, eval_function_block, eval_scoped_block, iterate,
+        methods::BuiltinMethod,
+        object::{CustomHash, HashKey, Object, Range, TailCall},
+        place_root, write_back,
+    },
+    lex::token::Span,
+    suggest::did_you_mean,
 };
 
 type Elements = Vec<Expression>;
 
 use super::{
     literal::Literal,
+    pattern::Pattern,
     statement::{Block, Identifier},
 };
 
@@ -31,6 +40,8 @@ pub enum Operator {
     LessThanOrEqual,
     And,
     Or,
+    NullCoalescing,
+    In,
 }
 
 impl Display for Operator {
@@ -48,6 +59,8 @@ impl Display for Operator {
             Operator::LessThanOrEqual => write!(f, "<="),
             Operator::And => write!(f, "&&"),
             Operator::Or => write!(f, "||"),
+            Operator::NullCoalescing => write!(f, "??"),
+            Operator::In => write!(f, "in"),
         }
     }
 }
@@ -71,7 +84,7 @@ impl Display for Prefix {
 pub enum Expression {
     Error(String),
     Literal(Literal),
-    Identifier(Identifier),
+    Identifier(Identifier, Span),
     Infix(Operator, Box<Expression>, Box<Expression>),
     Prefix(Prefix, Box<Expression>),
     If {
@@ -81,13 +94,18 @@ pub enum Expression {
     },
     Function {
         identifier: Option<Identifier>,
-        parameters: Vec<Identifier>,
+        parameters: Vec<Parameter>,
         body: Block,
     },
     Call {
         function: Box<Expression>,
         arguments: Vec<Expression>,
+        span: Span,
     },
+    NamedArgument(Identifier, Box<Expression>),
+    Spread(Box<Expression>),
+    /// `result?`, unwraps `Ok` or returns the `Err` from the current function.
+    Propagate(Box<Expression>),
     Array(Elements),
     Index {
         left: Box<Expression>,
@@ -95,143 +113,103 @@ pub enum Expression {
     },
     HashMap {
         pairs: BTreeMap<Expression, Expression>,
+        spreads: Elements,
     },
     Dot {
         identifier: Box<Expression>,
         attribute: Identifier,
     },
+    OptionalIndex {
+        left: Box<Expression>,
+        index: Box<Expression>,
+    },
+    OptionalDot {
+        identifier: Box<Expression>,
+        attribute: Identifier,
+    },
+    Match {
+        subject: Box<Expression>,
+        arms: Vec<MatchArm>,
+    },
+    Range {
+        start: Box<Expression>,
+        end: Box<Expression>,
+        step: Option<Box<Expression>>,
+        inclusive: bool,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Parameter {
+    pub pattern: Pattern,
+    pub default: Option<Expression>,
+    pub rest: bool,
+    pub span: Span,
+}
+
+impl Display for Parameter {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match (&self.default, self.rest) {
+            (_, true) => write!(f, "...{}", self.pattern),
+            (Some(d), _) => write!(f, "{} = {}", self.pattern, d),
+            (None, _) => write!(f, "{}", self.pattern),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct MatchArm {
+    pub pattern: Pattern,
+    pub guard: Option<Box<Expression>>,
+    pub body: Block,
 }
 
 impl Expression {
     pub fn eval(&self, env: &mut Environment) -> Object {
         match self {
-            Expression::Error(s) => Object::Error(s.clone()),
+            Expression::Error(s) => Object::error(ErrorKind::SyntaxError, s.clone()),
             Expression::Literal(l) => l.eval(),
             Expression::Prefix(op, exp) => {
                 let right_exp = exp.eval(env);
+                if right_exp.is_abrupt() {
+                    return right_exp;
+                }
 
                 match op {
                     Prefix::Bang => match right_exp {
                         Object::Boolean(b) => Object::Boolean(!b),
-                        _ => Object::Error(format!("expected Boolean, got: {}", right_exp)),
+                        _ => Object::error(
+                            ErrorKind::TypeError,
+                            format!("expected Boolean, got: {}", right_exp),
+                        ),
                     },
                     Prefix::Minus => match right_exp {
                         Object::Number(n) => Object::Number(-n),
-                        _ => Object::Error(format!("expected Number, got: {}", right_exp)),
+                        _ => Object::error(
+                            ErrorKind::TypeError,
+                            format!("expected Number, got: {}", right_exp),
+                        ),
                     },
                 }
             }
+            Expression::Infix(Operator::NullCoalescing, left_exp, right_exp) => {
+                match left_exp.eval(env) {
+                    Object::None => right_exp.eval(env),
+                    left => left,
+                }
+            }
             Expression::Infix(op, left_exp, right_exp) => {
-                let mut left = left_exp.eval(env);
-                let mut right = right_exp.eval(env);
-
-                loop {
-                    match (&left, &right) {
-                        (Object::Return(l), _) => {
-                            left = l.as_ref().clone();
-                        }
-                        (_, Object::Return(r)) => {
-                            right = r.as_ref().clone();
-                        }
-                        _ => break,
-                    };
+                let left = left_exp.eval(env);
+                if left.is_abrupt() {
+                    return left;
                 }
 
-                match (op, &left, &right) {
-                    (Operator::Plus, _, _) => match (&left, &right) {
-                        (Object::Number(l), Object::Number(r)) => Object::Number(l + r),
-                        _ => Object::Error(format!(
-                            "Can only perform operation + on numbers, got: {} and {} ",
-                            &left, &right,
-                        )),
-                    },
-                    (Operator::Minus, _, _) => match (&left, &right) {
-                        (Object::Number(l), Object::Number(r)) => Object::Number(l - r),
-                        _ => Object::Error(format!(
-                            "Can only perform operation {} on numbers, got: {} and {} ",
-                            op, &left, &right,
-                        )),
-                    },
-
-                    (Operator::Multiply, _, _) => match (&left, &right) {
-                        (Object::Number(l), Object::Number(r)) => Object::Number(l * r),
-                        _ => Object::Error(format!(
-                            "Can only perform operation {} on numbers, got: {} and {} ",
-                            op, left, right,
-                        )),
-                    },
-
-                    (Operator::Divide, _, _) => match (&left, &right) {
-                        (Object::Number(l), Object::Number(r)) => Object::Number(l / r),
-                        _ => Object::Error(format!(
-                            "Can only perform operation {} on numbers, got: {} and {} ",
-                            op, left, right,
-                        )),
-                    },
-
-                    (Operator::GreaterThan, _, _) => match (&left, &right) {
-                        (Object::Number(l), Object::Number(r)) => Object::Boolean(l > r),
-                        _ => Object::Error(format!(
-                            "Can only perform operation {} on numbers, got: {} and {} ",
-                            op, left, right,
-                        )),
-                    },
-
-                    (Operator::LessThan, _, _) => match (&left, &right) {
-                        (Object::Number(l), Object::Number(r)) => Object::Boolean(l < r),
-                        _ => Object::Error(format!(
-                            "Can only perform operation {} on numbers, got: {} and {} ",
-                            op, left, right,
-                        )),
-                    },
-
-                    (Operator::GreaterThanOrEqual, _, _) => match (&left, &right) {
-                        (Object::Number(l), Object::Number(r)) => Object::Boolean(l >= r),
-                        _ => Object::Error(format!(
-                            "Can only perform operation {} on numbers, got: {} and {} ",
-                            op, left, right,
-                        )),
-                    },
-
-                    (Operator::LessThanOrEqual, _, _) => match (&left, &right) {
-                        (Object::Number(l), Object::Number(r)) => Object::Boolean(l <= r),
-                        _ => Object::Error(format!(
-                            "Can only perform operation {} on numbers, got: {} and {} ",
-                            op, left, right,
-                        )),
-                    },
-
-                    (Operator::Equal, _, _) => match (&left, &right) {
-                        (Object::Number(l), Object::Number(r)) => Object::Boolean(l == r),
-                        (Object::Boolean(l), Object::Boolean(r)) => Object::Boolean(l == r),
-                        _ => Object::Error(format!(
-                            "Can only perform operation {} on (numbers | boolean), got: {} and {} ",
-                            op, left, right,
-                        )),
-                    },
-                    (Operator::NotEqual, _, _) => match (&left, &right) {
-                        (Object::Number(l), Object::Number(r)) => Object::Boolean(l != r),
-                        (Object::Boolean(l), Object::Boolean(r)) => Object::Boolean(l != r),
-                        _ => Object::Error(format!(
-                            "Can only perform operation {} on (numbers | boolean), got: {} and {} ",
-                            op, left, right,
-                        )),
-                    },
-                    (Operator::And, _, _) => match (&left, &right) {
-                        (Object::Boolean(l), Object::Boolean(r)) => Object::Boolean(*l && *r),
-                        _ => Object::Error(format!(
-                            "Can only perform operation {} on (numbers | boolean), got: {} and {} ",
-                            op, left, right,
-                        )),
-                    },
-                    (Operator::Or, _, _) => match (&left, &right) {
-                        (Object::Boolean(l), Object::Boolean(r)) => Object::Boolean(*l || *r),
-                        _ => Object::Error(format!(
-                            "Can only perform operation {} on (numbers | boolean), got: {} and {} ",
-                            op, left, right,
-                        )),
-                    },
+                let right = right_exp.eval(env);
+                if right.is_abrupt() {
+                    return right;
                 }
+
+                eval_infix_expression(op, left, right)
             }
             Expression::If {
                 condition,
@@ -240,171 +218,1323 @@ impl Expression {
             } => {
                 let condition_result = condition.eval(env);
                 match (condition_result, alternative) {
-                    (Object::Boolean(true), _) => eval_block(consequence, env),
-                    (Object::Boolean(false), Some(alt)) => eval_block(alt, env),
+                    (Object::Boolean(true), _) => eval_scoped_block(consequence, env),
+                    (Object::Boolean(false), Some(alt)) => eval_scoped_block(alt, env),
                     (Object::Boolean(false), None) => Object::None,
-                    (_, _) => Object::Error(String::from("condition did not evaluate to boolean")),
+                    (err, _) if err.is_abrupt() => err,
+                    (_, _) => Object::error(
+                        ErrorKind::TypeError,
+                        String::from("condition did not evaluate to boolean"),
+                    ),
                 }
             }
-            Expression::Identifier(ident) => match env.get(ident.clone()) {
+            Expression::Identifier(ident, _) => match env.get(ident.clone()) {
                 Some(obj) => obj,
-                None => Object::Error(format!("identifier not found: {}", ident)),
+                None => env.not_found(ident),
             },
             Expression::Function {
                 identifier,
                 parameters,
                 body,
             } => {
-                let fun = match identifier {
-                    Some(i) => Object::Function {
-                        name: Some(i.clone()),
-                        parameters: parameters.to_vec().clone(),
-                        body: body.to_vec().clone(),
-                    },
-                    None => Object::Function {
-                        name: None,
-                        parameters: parameters.to_vec().clone(),
-                        body: body.to_vec().clone(),
-                    },
+                let fun = Object::Function {
+                    name: identifier.clone(),
+                    parameters: parameters.to_vec(),
+                    body: body.to_vec(),
+                    env: env.clone(),
                 };
 
-                match identifier {
-                    Some(i) => env.set(i.clone(), fun.clone()),
-                    None => {}
+                if let Some(i) = identifier {
+                    env.set(i.clone(), fun.clone());
                 }
 
                 fun
             }
-            Expression::Call {
-                function,
-                arguments,
-            } => {
-                let func = function.eval(env);
-                let args = arguments
-                    .iter()
-                    .map(|arg| arg.eval(env))
-                    .collect::<Vec<_>>();
-
-                match (func, &args) {
-                    (
-                        Object::Function {
-                            name: _name,
-                            parameters,
-                            body,
-                        },
-                        _,
-                    ) => {
-                        let mut has_error = false;
-                        let mut error_idx = 0;
-                        for (idx, param) in parameters.iter().enumerate() {
-                            if let Some(arg) = args.get(idx) {
-                                env.set(param.clone(), arg.clone());
-                            } else {
-                                error_idx = idx;
-                                has_error = true;
-                                break;
-                            }
-                        }
-
-                        if has_error {
-                            return Object::Error(format!("Missing parameter: {}", error_idx));
-                        }
+            Expression::Call { .. }
+            | Expression::Index { .. }
+            | Expression::Dot { .. }
+            | Expression::OptionalIndex { .. }
+            | Expression::OptionalDot { .. } => self.eval_chain(env).unwrap_or(Object::None),
+            Expression::NamedArgument(name, _) => Object::error(
+                ErrorKind::TypeError,
+                format!("Named argument {} can only be used in a call", name),
+            ),
+            Expression::Spread(s) => Object::error(
+                ErrorKind::TypeError,
+                format!(
+                    "Spread {} can only be used in arrays, hashmaps and calls",
+                    s
+                ),
+            ),
+            Expression::Propagate(exp) => propagate(exp.eval(env)),
+            Expression::Array(elements) => {
+                let mut arr = vec![];
 
-                        match eval_function_block(&body, env) {
-                            Some(r) => r,
-                            None => Object::None,
-                        }
+                for el in elements {
+                    match el {
+                        Expression::Spread(s) => match iterate(s.eval(env)) {
+                            Ok(items) => arr.extend(items),
+                            Err(e) => return e,
+                        },
+                        _ => match el.eval(env) {
+                            err if err.is_abrupt() => return err,
+                            obj => arr.push(obj),
+                        },
                     }
-                    (Object::Builtin { func }, _) => func(args),
-                    (_, _) => Object::Error(format!("not a valid call {} ", self)),
                 }
-            }
-            Expression::Array(elements) => {
-                let arr = elements
-                    .iter()
-                    .map(|el| el.eval(env))
-                    .collect::<Vec<Object>>();
 
                 Object::Array(arr)
             }
-            Expression::Index { index, left } => {
-                let left_exp = left.eval(env);
-                let index_exp = index.eval(env);
-
-                match (&left_exp, &index_exp) {
-                    (Object::Array(arr), Object::Array(index)) => {
-                        if index.len() != 1 {
-                            return Object::Error(format!("invalid index, got {:?}", index));
-                        }
-
-                        match index.first().unwrap() {
-                            Object::Number(n) => match arr.get(*n as usize) {
-                                Some(obj) => obj.clone(),
-                                None => Object::None,
-                            },
-                            _ => Object::Error(format!("invalid index, got {:?}", index)),
-                        }
-                    }
-                    (Object::HashMap { pairs }, Object::Array(index)) => {
-                        if index.len() != 1 {
-                            return Object::Error(format!("invalid index, got {:?}", index));
-                        }
+            Expression::HashMap { pairs, spreads } => {
+                // enum keys hold objects with cells, but never hash or compare by them
+                #[allow(clippy::mutable_key_type)]
+                let mut hm: HashMap<HashKey, Object> = HashMap::new();
 
-                        match index.first().unwrap().hash() {
-                            Some(hk) => match pairs.get(&hk) {
-                                Some(v) => v.clone(),
-                                None => Object::None,
-                            },
-                            None => {
-                                Object::Error(format!("Index is not hashable, got {:?}", index))
-                            }
+                // Like struct update syntax, spreads are merged in order and
+                // explicit keys always win over spread ones
+                for s in spreads {
+                    match s.eval(env).thaw() {
+                        Object::HashMap { pairs } => hm.extend(pairs),
+                        err if err.is_abrupt() => return err,
+                        obj => {
+                            return Object::error(
+                                ErrorKind::TypeError,
+                                format!(
+                                    "Cannot spread {} into a hashmap, it is not a hashmap",
+                                    obj
+                                ),
+                            )
                         }
                     }
-                    _ => Object::Error(format!(
-                        "not supported, got: {:?}, {:?}",
-                        left_exp, index_exp
-                    )),
                 }
-            }
-            Expression::HashMap { pairs } => {
-                let mut hm: HashMap<HashKey, Object> = HashMap::new();
 
                 for (k, v) in pairs {
                     let key_obj = k.eval(env);
+                    if key_obj.is_abrupt() {
+                        return key_obj;
+                    }
 
                     if key_obj.hash().is_none() {
-                        return Object::Error(format!("Key is not hashable, got {}", key_obj));
+                        return Object::error(
+                            ErrorKind::TypeError,
+                            format!("Key is not hashable, got {}", key_obj),
+                        );
                     }
 
                     let key = key_obj.hash().unwrap();
                     let val = v.eval(env);
+                    if val.is_abrupt() {
+                        return val;
+                    }
 
                     hm.insert(key, val);
                 }
 
                 Object::HashMap { pairs: hm }
             }
+            Expression::Range {
+                start,
+                end,
+                step,
+                inclusive,
+            } => {
+                let start = start.eval(env);
+                if start.is_abrupt() {
+                    return start;
+                }
+
+                let end = end.eval(env);
+                if end.is_abrupt() {
+                    return end;
+                }
+
+                let step = match step {
+                    Some(s) => s.eval(env),
+                    None => Object::Number(1),
+                };
+                if step.is_abrupt() {
+                    return step;
+                }
+
+                match (start, end, step) {
+                    (_, _, Object::Number(0)) => Object::error(
+                        ErrorKind::ValueError,
+                        String::from("range step cannot be zero"),
+                    ),
+                    (Object::Number(start), Object::Number(end), Object::Number(step)) => {
+                        Object::Range(Range {
+                            start,
+                            end,
+                            step,
+                            inclusive: *inclusive,
+                        })
+                    }
+                    (start, end, step) => Object::error(
+                        ErrorKind::TypeError,
+                        format!(
+                            "Range bounds and step must be numbers, got: {}, {} and {}",
+                            start, end, step
+                        ),
+                    ),
+                }
+            }
+            Expression::Match { subject, arms } => {
+                let val = subject.eval(env);
+                if val.is_abrupt() {
+                    return val;
+                }
+
+                for arm in arms {
+                    let mut bindings = vec![];
+
+                    if !arm.pattern.matches(&val, &mut bindings) {
+                        continue;
+                    }
+
+                    // each arm binds into its own scope, dropped if the guard fails
+                    let mut scope = Environment::enclosed(env);
+
+                    for (name, obj) in bindings {
+                        scope.set(name, obj);
+                    }
+
+                    if let Some(guard) = &arm.guard {
+                        match guard.eval(&mut scope) {
+                            Object::Boolean(true) => {}
+                            Object::Boolean(false) => continue,
+                            err if err.is_abrupt() => return err,
+                            _ => {
+                                return Object::error(
+                                    ErrorKind::TypeError,
+                                    String::from("match guard did not evaluate to boolean"),
+                                )
+                            }
+                        }
+                    }
+
+                    return eval_block(&arm.body, &mut scope);
+                }
+
+                Object::error(
+                    ErrorKind::MatchError,
+                    format!("non-exhaustive match, no arm matched {}", val),
+                )
+            }
+        }
+    }
+
+    /// Evaluates `return f(...)` up to the call itself. Calls to user functions are
+    /// handed back as a `TailCall` for the caller to run, so tail recursion runs in
+    /// constant stack space.
+    pub fn eval_tail_call(&self, env: &mut Environment) -> Object {
+        let (function, arguments, span) = match self {
+            Expression::Call {
+                function,
+                arguments,
+                span,
+            } if !matches!(
+                function.as_ref(),
+                Expression::Dot { .. } | Expression::OptionalDot { .. }
+            ) =>
+            {
+                (function, arguments, span)
+            }
+            _ => return self.eval(env),
+        };
+
+        let func = match function.eval_chain(env) {
+            Some(func @ Object::Function { .. }) => func,
+            Some(func) => return eval_call(func, function, arguments, *span, env),
+            None => return Object::None,
+        };
+
+        match eval_arguments(arguments, env) {
+            Ok((positional, named)) => Object::TailCall(Box::new(TailCall {
+                func,
+                function: function.as_ref().clone(),
+                positional,
+                named,
+                span: *span,
+            })),
+            Err(e) => e,
+        }
+    }
+
+    /// Evaluates a chain of calls, indexes and dot operations. `None` means an
+    /// optional operation (`?.` or `?[`) short-circuited somewhere in the chain.
+    fn eval_chain(&self, env: &mut Environment) -> Option<Object> {
+        match self {
+            Expression::Call {
+                function,
+                arguments,
+                span,
+            } => match function.as_ref() {
+                Expression::Dot {
+                    identifier,
+                    attribute,
+                } => {
+                    let receiver = identifier.eval_chain(env)?;
+                    Some(eval_method_call(
+                        receiver, identifier, attribute, function, arguments, *span, env,
+                    ))
+                }
+                Expression::OptionalDot {
+                    identifier,
+                    attribute,
+                } => {
+                    let receiver = identifier.eval_chain(env)?;
+                    let is_result = is_result(&receiver);
+
+                    match unwrap_optional(receiver)? {
+                        abrupt if abrupt.is_abrupt() => Some(abrupt),
+                        // the value of a Result is not stored anywhere, nothing to update
+                        receiver if is_result => Some(eval_method_call(
+                            receiver, function, attribute, function, arguments, *span, env,
+                        )),
+                        receiver => Some(eval_method_call(
+                            receiver, identifier, attribute, function, arguments, *span, env,
+                        )),
+                    }
+                }
+                _ => {
+                    let func = function.eval_chain(env)?;
+                    Some(eval_call(func, function, arguments, *span, env))
+                }
+            },
+            Expression::Index { left, index } => {
+                let left_exp = left.eval_chain(env)?;
+                Some(eval_index(left_exp, index.eval(env)))
+            }
             Expression::Dot {
                 identifier,
                 attribute,
             } => {
-                // For now "dot" operations only works on hashMaps
-                let hashmap = identifier.eval(env);
-
-                match hashmap {
-                    Object::HashMap { pairs } => {
-                        match pairs.get(&HashKey::new(attribute.clone())) {
-                            Some(v) => v.clone(),
-                            None => Object::None,
-                        }
-                    }
-                    _ => Object::Error(format!(
-                        "Cannot read {:?} propertie of {}",
-                        attribute, identifier
-                    )),
+                let obj = identifier.eval_chain(env)?;
+                Some(eval_dot(obj, attribute))
+            }
+            Expression::OptionalIndex { left, index } => {
+                match unwrap_optional(left.eval_chain(env)?)? {
+                    abrupt if abrupt.is_abrupt() => Some(abrupt),
+                    left_exp => Some(eval_index(left_exp, index.eval(env))),
                 }
             }
+            Expression::OptionalDot {
+                identifier,
+                attribute,
+            } => match unwrap_optional(identifier.eval_chain(env)?)? {
+                abrupt if abrupt.is_abrupt() => Some(abrupt),
+                obj => Some(eval_dot(obj, attribute)),
+            },
+            _ => Some(self.eval(env)),
+        }
+    }
+}
+
+/// `r?` unwraps an `Ok`, and returns an `Err` from the current function.
+fn propagate(obj: Object) -> Object {
+    match obj {
+        obj if obj.is_abrupt() => obj,
+        Object::Enum {
+            name,
+            variant,
+            mut values,
+            methods,
+        } if name == "Result" => match variant.as_str() {
+            "Ok" => values.pop().map(|(_, v)| v).unwrap_or(Object::None),
+            _ => Object::Return(Box::new(Object::Enum {
+                name,
+                variant,
+                values,
+                methods,
+            })),
+        },
+        obj => Object::error(
+            ErrorKind::TypeError,
+            format!("? expects a Result, got {}", obj),
+        ),
+    }
+}
+
+fn is_result(obj: &Object) -> bool {
+    matches!(obj, Object::Enum { name, .. } if name == "Result")
+}
+
+/// What `?.` and `?[` work on, `None` short-circuits the chain. The lexer reads
+/// `r?.x` and `r?[0]` as optional chaining, so on a Result they unwrap it like
+/// `r?` would before reading from it.
+fn unwrap_optional(obj: Object) -> Option<Object> {
+    match obj {
+        Object::None => None,
+        obj if is_result(&obj) => Some(propagate(obj)),
+        obj => Some(obj),
+    }
+}
+
+fn eval_call(
+    func: Object,
+    function: &Expression,
+    arguments: &[Expression],
+    span: Span,
+    env: &mut Environment,
+) -> Object {
+    match eval_arguments(arguments, env) {
+        Ok((positional, named)) => apply(func, Some(function), positional, named, span, None),
+        Err(e) => e,
+    }
+}
+
+/// Calls `receiver.attribute(...)`. Methods of user types get the receiver as `self`,
+/// when they change it the new value is stored back where the receiver came from.
+fn eval_method_call(
+    receiver: Object,
+    identifier: &Expression,
+    attribute: &Identifier,
+    function: &Expression,
+    arguments: &[Expression],
+    span: Span,
+    env: &mut Environment,
+) -> Object {
+    let (positional, named) = match eval_arguments(arguments, env) {
+        Ok(args) => args,
+        Err(e) => return e,
+    };
+
+    let (method, takes_self) = match find_method(&receiver, attribute) {
+        Some(m) => m,
+        None => {
+            return eval_builtin_method_call(
+                receiver, identifier, attribute, function, positional, named, span, env,
+            )
         }
+    };
+
+    if !takes_self {
+        return apply(method, Some(function), positional, named, span, None);
+    }
+
+    let mut receiver = Receiver {
+        this: receiver,
+        updated: None,
+    };
+    let result = apply(
+        method,
+        Some(function),
+        positional,
+        named,
+        span,
+        Some(&mut receiver),
+    );
+
+    if result.is_error() {
+        return result;
+    }
+
+    match receiver.updated {
+        Some(this) => match write_back(identifier, this, env) {
+            Ok(()) => result,
+            Err(e) => e,
+        },
+        None => result,
+    }
+}
+
+/// Calls a method of a builtin type like `xs.push(4)`, otherwise calls the attribute
+/// itself, such as a function stored in a hashmap.
+#[allow(clippy::too_many_arguments)]
+fn eval_builtin_method_call(
+    receiver: Object,
+    identifier: &Expression,
+    attribute: &Identifier,
+    function: &Expression,
+    positional: Vec<Object>,
+    named: Vec<(Identifier, Object)>,
+    span: Span,
+    env: &mut Environment,
+) -> Object {
+    // hashmap entries win over methods of the same name
+    let is_entry = match receiver.thawed() {
+        Object::HashMap { pairs } => pairs.contains_key(&HashKey::new(attribute.clone())),
+        _ => false,
+    };
+
+    let method = match (
+        BuiltinMethod::lookup(&receiver, attribute),
+        receiver.thawed(),
+    ) {
+        (Some(method), _) if !is_entry => method,
+        (None, Object::HashMap { pairs }) if !is_entry => {
+            let keys = pairs.keys().map(HashKey::as_str);
+            let names = keys.chain(BuiltinMethod::names(&receiver).iter().copied());
+
+            let error = Object::error(
+                ErrorKind::AttributeError,
+                format!(
+                    "hashmap has no entry or method {}{}",
+                    attribute,
+                    did_you_mean(attribute, names)
+                ),
+            );
+
+            return located(error, span);
+        }
+        _ => {
+            let func = eval_dot(receiver, attribute);
+            return apply(func, Some(function), positional, named, span, None);
+        }
+    };
+
+    if !named.is_empty() {
+        return Object::error(
+            ErrorKind::TypeError,
+            format!(
+                "Builtin functions do not accept named arguments at {}",
+                span
+            ),
+        );
+    }
+
+    let mut this = receiver;
+
+    if !BuiltinMethod::mutates(attribute) {
+        return located(method.call(attribute, &mut this, positional), span);
+    }
+
+    if let Some(root) = place_root(identifier) {
+        if let Err(e) = env.check_assign(root) {
+            return located(e, span);
+        }
+    }
+
+    let result = located(method.call(attribute, &mut this, positional), span);
+
+    if result.is_error() {
+        return result;
+    }
+
+    match write_back(identifier, this, env) {
+        Ok(()) => result,
+        Err(e) => e,
+    }
+}
+
+/// Looks up a method of a user type, the flag tells whether it takes the receiver
+/// as `self`. Fields and variants win over methods with the same name.
+fn find_method(receiver: &Object, attribute: &Identifier) -> Option<(Object, bool)> {
+    let (methods, takes_self) = match receiver.thawed() {
+        Object::Struct {
+            fields, methods, ..
+        } if !fields.iter().any(|(f, _)| f == attribute) => (methods, true),
+        Object::Enum {
+            values, methods, ..
+        } if !values.iter().any(|(f, _)| f == attribute) => (methods, true),
+        Object::StructType { methods, .. } => (methods, false),
+        Object::EnumType {
+            variants, methods, ..
+        } if !variants.iter().any(|(v, _)| v == attribute) => (methods, false),
+        _ => return None,
+    };
+
+    let method = methods.borrow().get(attribute).cloned()?;
+
+    Some((method, takes_self))
+}
+
+type Arguments = (Vec<Object>, Vec<(Identifier, Object)>);
+
+fn eval_arguments(arguments: &[Expression], env: &mut Environment) -> Result<Arguments, Object> {
+    let mut positional = vec![];
+    let mut named = vec![];
+
+    for arg in arguments {
+        match arg {
+            Expression::NamedArgument(name, value) => match value.eval(env) {
+                err if err.is_abrupt() => return Err(err),
+                obj => named.push((name.clone(), obj)),
+            },
+            Expression::Spread(s) => positional.extend(iterate(s.eval(env))?),
+            _ => match arg.eval(env) {
+                err if err.is_abrupt() => return Err(err),
+                obj => positional.push(obj),
+            },
+        }
+    }
+
+    Ok((positional, named))
+}
+
+/// Calls a function value from a builtin, like the callback given to `map`.
+pub fn call_function(func: Object, args: Vec<Object>) -> Object {
+    apply(func, None, args, vec![], Span::default(), None)
+}
+
+/// Runs the call pending in a `return f(...)` outside of any function.
+pub fn run_tail_call(obj: Object) -> Object {
+    match obj {
+        Object::TailCall(call) => {
+            let TailCall {
+                func,
+                function,
+                positional,
+                named,
+                span,
+            } = *call;
+
+            apply(func, Some(&function), positional, named, span, None)
+        }
+        obj => obj,
+    }
+}
+
+/// The object a method is called on, with what `self` holds once the call is done
+/// if the method assigned to it.
+struct Receiver {
+    this: Object,
+    updated: Option<Object>,
+}
+
+/// Calls `func` with evaluated arguments. A method receives `receiver` as `self`.
+fn apply(
+    func: Object,
+    function: Option<&Expression>,
+    positional: Vec<Object>,
+    named: Vec<(Identifier, Object)>,
+    span: Span,
+    receiver: Option<&mut Receiver>,
+) -> Object {
+    let call_site = span;
+    let mut result = apply_once(func, function, positional, named, span, receiver);
+
+    // the frame that returned a tail call is gone by now, running the call here
+    // instead of inside it keeps the stack flat
+    while let Object::TailCall(call) = result {
+        let TailCall {
+            func,
+            function,
+            positional,
+            named,
+            span,
+        } = *call;
+
+        result = apply_once(func, Some(&function), positional, named, span, None);
+    }
+
+    located(result, call_site)
+}
+
+// Errors raised during a call without a location of their own point at its call site
+fn located(mut result: Object, span: Span) -> Object {
+    if let Object::Error(err) = &mut result {
+        if err.span.is_none() && span != Span::default() {
+            err.span = Some(span);
+        }
+    }
+
+    result
+}
+
+fn apply_once(
+    func: Object,
+    function: Option<&Expression>,
+    positional: Vec<Object>,
+    named: Vec<(Identifier, Object)>,
+    span: Span,
+    receiver: Option<&mut Receiver>,
+) -> Object {
+    match func {
+        Object::Function {
+            name,
+            parameters,
+            body,
+            env: closure_env,
+        } => {
+            // `let add = fn(x) {}` has no name of its own, use the one it was called by
+            let name = match (name, function) {
+                (Some(n), _) => Some(n),
+                (None, Some(Expression::Identifier(i, _))) => Some(i.clone()),
+                (None, _) => None,
+            };
+
+            let calls = Rc::clone(&closure_env.calls);
+
+            if calls.borrow_mut().is_full() {
+                return Object::error(
+                    ErrorKind::RecursionError,
+                    format!(
+                        "maximum recursion depth exceeded in {} at {}",
+                        name.as_deref().unwrap_or("<anonymous>"),
+                        span
+                    ),
+                );
+            }
+
+            let mut call_env = Environment::enclosed(&closure_env);
+
+            if let Some(receiver) = &receiver {
+                call_env.set(String::from("self"), receiver.this.clone());
+            }
+
+            let args = match calls.borrow().verbose {
+                true => Some(
+                    positional
+                        .iter()
+                        .map(|arg| arg.to_string())
+                        .chain(named.iter().map(|(k, v)| format!("{}: {}", k, v)))
+                        .collect(),
+                ),
+                false => None,
+            };
+            calls.borrow_mut().frames.push(Frame {
+                name: name.clone(),
+                span,
+                args,
+            });
+
+            let result =
+                match bind_arguments(&name, &parameters, positional, named, span, &mut call_env) {
+                    Ok(()) => eval_function_block(&body, &mut call_env).unwrap_or(Object::None),
+                    Err(e) => e,
+                };
+
+            if result.is_error() {
+                calls.borrow_mut().record(&result);
+            }
+
+            calls.borrow_mut().frames.pop();
+
+            // reading `self` leaves it alone, so constants can call such methods
+            if let Some(receiver) = receiver {
+                if call_env.is_assigned("self") {
+                    receiver.updated = call_env.get(String::from("self"));
+                }
+            }
+
+            result
+        }
+        Object::StructType {
+            name,
+            fields,
+            methods,
+        } => match bind_fields(&name, &fields, positional, named, span) {
+            Ok(values) => Object::Struct {
+                name,
+                fields: values,
+                methods,
+            },
+            Err(e) => e,
+        },
+        Object::EnumVariant {
+            name,
+            variant,
+            fields,
+            methods,
+        } => {
+            let constructor = format!("{}.{}", name, variant);
+
+            match bind_fields(&constructor, &fields, positional, named, span) {
+                Ok(values) => Object::Enum {
+                    name,
+                    variant,
+                    values,
+                    methods,
+                },
+                Err(e) => e,
+            }
+        }
+        Object::Builtin { func } => match named.is_empty() {
+            true => func(positional),
+            false => Object::error(
+                ErrorKind::TypeError,
+                format!(
+                    "Builtin functions do not accept named arguments at {}",
+                    span
+                ),
+            ),
+        },
+        err if err.is_abrupt() => err,
+        obj => match function {
+            Some(f) => Object::error(ErrorKind::TypeError, format!("not a valid call {} ", f)),
+            None => Object::error(ErrorKind::TypeError, format!("not a valid call {} ", obj)),
+        },
+    }
+}
+
+fn signature(name: &Option<Identifier>, parameters: &[Parameter]) -> String {
+    let params = parameters
+        .iter()
+        .map(|p| match (&p.default, p.rest) {
+            (_, true) => format!("...{}", p.pattern),
+            (Some(_), _) => format!("{}?", p.pattern),
+            (None, _) => p.pattern.to_string(),
+        })
+        .collect::<Vec<_>>()
+        .join(", ");
+
+    match name {
+        Some(n) => format!("fn {}({})", n, params),
+        None => format!("fn({})", params),
+    }
+}
+
+/// Binds positional and named arguments to the parameters in a fresh call scope,
+/// filling in defaults and collecting the rest parameter.
+fn bind_arguments(
+    name: &Option<Identifier>,
+    parameters: &[Parameter],
+    positional: Vec<Object>,
+    mut named: Vec<(Identifier, Object)>,
+    span: Span,
+    call_env: &mut Environment,
+) -> Result<(), Object> {
+    let fixed = parameters.iter().filter(|p| !p.rest).count();
+    let mut positional = positional.into_iter();
+
+    // a misspelled name would otherwise be reported as the argument it failed to fill
+    let unknown = named.iter().find(|(n, _)| {
+        !parameters
+            .iter()
+            .any(|p| !p.rest && matches!(&p.pattern, Pattern::Identifier(i) if i == n))
+    });
+
+    if let Some((n, _)) = unknown {
+        return Err(unknown_argument(n, name, parameters, span));
+    }
+
+    for param in parameters {
+        let param_name = match &param.pattern {
+            Pattern::Identifier(i) => Some(i),
+            _ => None,
+        };
+
+        if param.rest {
+            let others = positional.by_ref().collect();
+            param.pattern.bind(Object::Array(others), call_env)?;
+            continue;
+        }
+
+        let by_name = named
+            .iter()
+            .position(|(n, _)| Some(n) == param_name)
+            .map(|idx| named.remove(idx).1);
+
+        let val = match (positional.next(), by_name) {
+            (Some(_), Some(_)) => {
+                return Err(Object::error(
+                    ErrorKind::ArityError,
+                    format!(
+                        "Argument {} for {} was given more than once at {}",
+                        param.pattern,
+                        signature(name, parameters),
+                        span
+                    ),
+                ))
+            }
+            (Some(v), None) | (None, Some(v)) => v,
+            (None, None) => match &param.default {
+                Some(d) => match d.eval(call_env) {
+                    val if val.is_abrupt() => return Err(val),
+                    val => val,
+                },
+                None => {
+                    return Err(Object::error(
+                        ErrorKind::ArityError,
+                        format!(
+                            "Missing argument for parameter {} of {} at {}",
+                            param.pattern,
+                            signature(name, parameters),
+                            span
+                        ),
+                    ))
+                }
+            },
+        };
+
+        param.pattern.bind(val, call_env)?;
+    }
+
+    let extra = positional.count();
+    if extra > 0 {
+        return Err(Object::error(
+            ErrorKind::ArityError,
+            format!(
+                "Too many arguments for {}: expected at most {}, got {} at {}",
+                signature(name, parameters),
+                fixed,
+                fixed + extra,
+                span
+            ),
+        ));
+    }
+
+    if let Some((n, _)) = named.first() {
+        return Err(unknown_argument(n, name, parameters, span));
+    }
+
+    Ok(())
+}
+
+fn unknown_argument(
+    argument: &Identifier,
+    name: &Option<Identifier>,
+    parameters: &[Parameter],
+    span: Span,
+) -> Object {
+    Object::error(
+        ErrorKind::ArityError,
+        format!(
+            "Unknown argument {} for {} at {}",
+            argument,
+            signature(name, parameters),
+            span
+        ),
+    )
+}
+
+/// Pairs the fields of a struct or enum variant with positional and named
+/// values, every field is required.
+fn bind_fields(
+    name: &str,
+    fields: &[Identifier],
+    positional: Vec<Object>,
+    mut named: Vec<(Identifier, Object)>,
+    span: Span,
+) -> Result<Vec<(Identifier, Object)>, Object> {
+    let mut values = vec![];
+    let mut positional = positional.into_iter();
+
+    for field in fields {
+        let by_name = named
+            .iter()
+            .position(|(n, _)| n == field)
+            .map(|idx| named.remove(idx).1);
+
+        let val = match (positional.next(), by_name) {
+            (Some(_), Some(_)) => {
+                return Err(Object::error(
+                    ErrorKind::ArityError,
+                    format!(
+                        "Field {} of {} was given more than once at {}",
+                        field, name, span
+                    ),
+                ))
+            }
+            (Some(v), None) | (None, Some(v)) => v,
+            (None, None) => {
+                return Err(Object::error(
+                    ErrorKind::ArityError,
+                    format!("Missing field {} for {} at {}", field, name, span),
+                ))
+            }
+        };
+
+        values.push((field.clone(), val));
+    }
+
+    let extra = positional.count();
+    if extra > 0 {
+        return Err(Object::error(
+            ErrorKind::ArityError,
+            format!(
+                "Too many fields for {}: expected {}, got {} at {}",
+                name,
+                fields.len(),
+                fields.len() + extra,
+                span
+            ),
+        ));
+    }
+
+    if let Some((n, _)) = named.first() {
+        return Err(Object::error(
+            ErrorKind::ArityError,
+            format!("{} has no field {} at {}", name, n, span),
+        ));
+    }
+
+    Ok(values)
+}
+
+/// Index expressions keep their index wrapped in a one element array.
+fn index_type_name(index: &Object) -> String {
+    match index {
+        Object::Array(index) if index.len() == 1 => index[0].type_name(),
+        other => other.type_name(),
+    }
+}
+
+fn eval_index(left_exp: Object, index_exp: Object) -> Object {
+    if left_exp.is_abrupt() {
+        return left_exp;
+    }
+
+    if index_exp.is_abrupt() {
+        return index_exp;
+    }
+
+    match (left_exp.thawed(), &index_exp) {
+        (Object::Array(arr), Object::Array(index)) => {
+            if index.len() != 1 {
+                return Object::error(
+                    ErrorKind::IndexError,
+                    format!("Cannot index with {} values", index.len()),
+                );
+            }
+
+            match index.first().unwrap() {
+                Object::Number(n) => match arr.get(*n as usize) {
+                    Some(obj) => obj.clone(),
+                    None => Object::None,
+                },
+                Object::Range(r) => Object::Array(
+                    r.iter_within(arr.len() as i64)
+                        .map(|i| arr[i as usize].clone())
+                        .collect(),
+                ),
+                _ => Object::error(
+                    ErrorKind::TypeError,
+                    format!("Cannot index array with {}", index_type_name(&index_exp)),
+                ),
+            }
+        }
+        (Object::Range(r), Object::Array(index)) => match index.as_slice() {
+            [Object::Number(n)] => match r.get(*n) {
+                Some(v) => Object::Number(v),
+                None => Object::None,
+            },
+            _ => Object::error(
+                ErrorKind::TypeError,
+                format!("Cannot index range with {}", index_type_name(&index_exp)),
+            ),
+        },
+        (Object::HashMap { pairs }, Object::Array(index)) => {
+            if index.len() != 1 {
+                return Object::error(
+                    ErrorKind::IndexError,
+                    format!("Cannot index with {} values", index.len()),
+                );
+            }
+
+            match index.first().unwrap().hash() {
+                Some(hk) => match pairs.get(&hk) {
+                    Some(v) => v.clone(),
+                    None => Object::None,
+                },
+                None => Object::error(
+                    ErrorKind::TypeError,
+                    format!("{} is not a valid key", index[0].type_name()),
+                ),
+            }
+        }
+        _ => Object::error(
+            ErrorKind::TypeError,
+            format!(
+                "Cannot index {} with {}",
+                left_exp.type_name(),
+                index_type_name(&index_exp)
+            ),
+        ),
+    }
+}
+
+fn eval_dot(obj: Object, attribute: &Identifier) -> Object {
+    let type_name = obj.type_name();
+    let builtin_methods = BuiltinMethod::names(&obj);
+
+    match obj.thaw() {
+        err if err.is_abrupt() => err,
+        Object::HashMap { pairs } => match pairs.get(&HashKey::new(attribute.clone())) {
+            Some(v) => v.clone(),
+            None => Object::None,
+        },
+        Object::Struct {
+            name,
+            fields,
+            methods,
+        } => match fields.iter().find(|(f, _)| f == attribute) {
+            Some((_, v)) => v.clone(),
+            None => {
+                let methods = methods.borrow();
+                let names = fields.iter().map(|(f, _)| f).chain(methods.keys());
+
+                Object::error(
+                    ErrorKind::AttributeError,
+                    format!(
+                        "{} has no field {}{}",
+                        name,
+                        attribute,
+                        did_you_mean(attribute, names.map(String::as_str))
+                    ),
+                )
+            }
+        },
+        Object::Module { name, exports } => match exports.get(attribute) {
+            Some(v) => v.clone(),
+            None => Object::error(
+                ErrorKind::AttributeError,
+                format!(
+                    "Module {} has no export {}{}",
+                    name,
+                    attribute,
+                    did_you_mean(attribute, exports.keys().map(String::as_str))
+                ),
+            ),
+        },
+        Object::StructType { name, methods, .. } => match methods.borrow().get(attribute) {
+            Some(method) => method.clone(),
+            None => Object::error(
+                ErrorKind::AttributeError,
+                format!(
+                    "{} has no function {}{}",
+                    name,
+                    attribute,
+                    did_you_mean(attribute, methods.borrow().keys().map(String::as_str))
+                ),
+            ),
+        },
+        Object::EnumType {
+            name,
+            variants,
+            methods,
+        } => match variants.iter().find(|(v, _)| v == attribute) {
+            Some((variant, fields)) if fields.is_empty() => Object::Enum {
+                name,
+                variant: variant.clone(),
+                values: vec![],
+                methods,
+            },
+            Some((variant, fields)) => Object::EnumVariant {
+                name,
+                variant: variant.clone(),
+                fields: fields.clone(),
+                methods,
+            },
+            None => match methods.borrow().get(attribute) {
+                Some(method) => method.clone(),
+                None => Object::error(
+                    ErrorKind::AttributeError,
+                    format!(
+                        "{} has no variant {}{}",
+                        name,
+                        attribute,
+                        did_you_mean(
+                            attribute,
+                            variants
+                                .iter()
+                                .map(|(v, _)| v)
+                                .chain(methods.borrow().keys())
+                                .map(String::as_str)
+                        )
+                    ),
+                ),
+            },
+        },
+        Object::Enum {
+            name,
+            variant,
+            values,
+            ..
+        } => match values.iter().find(|(f, _)| f == attribute) {
+            Some((_, v)) => v.clone(),
+            None => Object::error(
+                ErrorKind::AttributeError,
+                format!(
+                    "{}.{} has no field {}{}",
+                    name,
+                    variant,
+                    attribute,
+                    did_you_mean(attribute, values.iter().map(|(f, _)| f.as_str()))
+                ),
+            ),
+        },
+        _ => Object::error(
+            ErrorKind::TypeError,
+            format!(
+                "Cannot read {} of {}{}",
+                attribute,
+                type_name,
+                did_you_mean(attribute, builtin_methods.iter().copied())
+            ),
+        ),
+    }
+}
+
+pub fn eval_infix_expression(op: &Operator, left: Object, right: Object) -> Object {
+    match (op, &left, &right) {
+        (Operator::Plus, _, _) => match (&left, &right) {
+            (Object::Number(l), Object::Number(r)) => Object::Number(l + r),
+            _ => Object::error(ErrorKind::TypeError, format!(
+                "Can only perform operation + on numbers, got: {} and {} ",
+                &left, &right,
+            )),
+        },
+        (Operator::Minus, _, _) => match (&left, &right) {
+            (Object::Number(l), Object::Number(r)) => Object::Number(l - r),
+            _ => Object::error(ErrorKind::TypeError, format!(
+                "Can only perform operation {} on numbers, got: {} and {} ",
+                op, &left, &right,
+            )),
+        },
+
+        (Operator::Multiply, _, _) => match (&left, &right) {
+            (Object::Number(l), Object::Number(r)) => Object::Number(l * r),
+            _ => Object::error(ErrorKind::TypeError, format!(
+                "Can only perform operation {} on numbers, got: {} and {} ",
+                op, left, right,
+            )),
+        },
+
+        (Operator::Divide, _, _) => match (&left, &right) {
+            (Object::Number(_), Object::Number(0)) => Object::error(ErrorKind::DivisionByZero, String::from("division by zero")),
+            (Object::Number(l), Object::Number(r)) => Object::Number(l / r),
+            _ => Object::error(ErrorKind::TypeError, format!(
+                "Can only perform operation {} on numbers, got: {} and {} ",
+                op, left, right,
+            )),
+        },
+
+        (Operator::GreaterThan, _, _) => match (&left, &right) {
+            (Object::Number(l), Object::Number(r)) => Object::Boolean(l > r),
+            _ => Object::error(ErrorKind::TypeError, format!(
+                "Can only perform operation {} on numbers, got: {} and {} ",
+                op, left, right,
+            )),
+        },
+
+        (Operator::LessThan, _, _) => match (&left, &right) {
+            (Object::Number(l), Object::Number(r)) => Object::Boolean(l < r),
+            _ => Object::error(ErrorKind::TypeError, format!(
+                "Can only perform operation {} on numbers, got: {} and {} ",
+                op, left, right,
+            )),
+        },
+
+        (Operator::GreaterThanOrEqual, _, _) => match (&left, &right) {
+            (Object::Number(l), Object::Number(r)) => Object::Boolean(l >= r),
+            _ => Object::error(ErrorKind::TypeError, format!(
+                "Can only perform operation {} on numbers, got: {} and {} ",
+                op, left, right,
+            )),
+        },
+
+        (Operator::LessThanOrEqual, _, _) => match (&left, &right) {
+            (Object::Number(l), Object::Number(r)) => Object::Boolean(l <= r),
+            _ => Object::error(ErrorKind::TypeError, format!(
+                "Can only perform operation {} on numbers, got: {} and {} ",
+                op, left, right,
+            )),
+        },
+
+        (Operator::Equal, _, _) => match (&left, &right) {
+            (Object::Number(l), Object::Number(r)) => Object::Boolean(l == r),
+            (Object::Boolean(l), Object::Boolean(r)) => Object::Boolean(l == r),
+            (Object::Enum { .. }, _) | (_, Object::Enum { .. }) => {
+                Object::Boolean(is_same(&left, &right))
+            }
+            _ => Object::error(ErrorKind::TypeError, format!(
+                "Can only perform operation {} on (numbers | boolean), got: {} and {} ",
+                op, left, right,
+            )),
+        },
+        (Operator::NotEqual, _, _) => match (&left, &right) {
+            (Object::Number(l), Object::Number(r)) => Object::Boolean(l != r),
+            (Object::Boolean(l), Object::Boolean(r)) => Object::Boolean(l != r),
+            (Object::Enum { .. }, _) | (_, Object::Enum { .. }) => {
+                Object::Boolean(!is_same(&left, &right))
+            }
+            _ => Object::error(ErrorKind::TypeError, format!(
+                "Can only perform operation {} on (numbers | boolean), got: {} and {} ",
+                op, left, right,
+            )),
+        },
+        (Operator::And, _, _) => match (&left, &right) {
+            (Object::Boolean(l), Object::Boolean(r)) => Object::Boolean(*l && *r),
+            _ => Object::error(ErrorKind::TypeError, format!(
+                "Can only perform operation {} on (numbers | boolean), got: {} and {} ",
+                op, left, right,
+            )),
+        },
+        (Operator::Or, _, _) => match (&left, &right) {
+            (Object::Boolean(l), Object::Boolean(r)) => Object::Boolean(*l || *r),
+            _ => Object::error(ErrorKind::TypeError, format!(
+                "Can only perform operation {} on (numbers | boolean), got: {} and {} ",
+                op, left, right,
+            )),
+        },
+        (Operator::In, _, _) => match (&left, right.thawed()) {
+            (Object::Number(n), Object::Range(r)) => Object::Boolean(r.contains(*n)),
+            (_, Object::Array(arr)) => Object::Boolean(arr.iter().any(|el| is_same(&left, el))),
+            (_, Object::HashMap { pairs }) => match left.hash() {
+                Some(hk) => Object::Boolean(pairs.contains_key(&hk)),
+                None => Object::error(ErrorKind::TypeError, format!("Key is not hashable, got {}", left)),
+            },
+            (Object::String(sub), Object::String(s)) => Object::Boolean(s.contains(sub.as_str())),
+            _ => Object::error(ErrorKind::TypeError, format!(
+                "Can only perform operation {} on (range | array | hashmap | string), got: {} and {} ",
+                op, left, right,
+            )),
+        },
+        (Operator::NullCoalescing, Object::None, _) => right,
+        (Operator::NullCoalescing, _, _) => left,
+    }
+}
+
+pub fn is_same(left: &Object, right: &Object) -> bool {
+    match (left, right) {
+        (Object::Number(l), Object::Number(r)) => l == r,
+        (Object::String(l), Object::String(r)) => l == r,
+        (Object::Boolean(l), Object::Boolean(r)) => l == r,
+        (Object::None, Object::None) => true,
+        (Object::Range(l), Object::Range(r)) => l == r,
+        (Object::Frozen(l), Object::Frozen(r)) => is_same(l, r),
+        (Object::Array(l), Object::Array(r)) => {
+            l.len() == r.len() && l.iter().zip(r.iter()).all(|(l, r)| is_same(l, r))
+        }
+        (Object::HashMap { pairs: l }, Object::HashMap { pairs: r }) => {
+            l.len() == r.len()
+                && l.iter()
+                    .all(|(k, l)| r.get(k).is_some_and(|r| is_same(l, r)))
+        }
+        (
+            Object::Struct {
+                name: l_name,
+                fields: l_fields,
+                ..
+            },
+            Object::Struct {
+                name: r_name,
+                fields: r_fields,
+                ..
+            },
+        ) => {
+            l_name == r_name
+                && l_fields.len() == r_fields.len()
+                && l_fields
+                    .iter()
+                    .zip(r_fields.iter())
+                    .all(|((lf, l), (rf, r))| lf == rf && is_same(l, r))
+        }
+        (
+            Object::Enum {
+                name: l_name,
+                variant: l_variant,
+                values: l_values,
+                ..
+            },
+            Object::Enum {
+                name: r_name,
+                variant: r_variant,
+                values: r_values,
+                ..
+            },
+        ) => {
+            l_name == r_name
+                && l_variant == r_variant
+                && l_values.len() == r_values.len()
+                && l_values
+                    .iter()
+                    .zip(r_values.iter())
+                    .all(|((_, l), (_, r))| is_same(l, r))
+        }
+        _ => false,
     }
 }
 
@@ -415,7 +1545,7 @@ impl Display for Expression {
             Expression::Literal(Literal::Number(v)) => write!(f, "Number ({})", v),
             Expression::Literal(Literal::String(s)) => write!(f, "String ({})", s),
             Expression::Literal(Literal::Boolean(b)) => write!(f, "Bool ({})", b),
-            Expression::Identifier(i) => write!(f, "Ident ({})", i),
+            Expression::Identifier(i, _) => write!(f, "Ident ({})", i),
             Expression::Infix(op, left, right) => {
                 write!(f, "{} Left {} , Right {}", op, left, right)
             }
@@ -458,7 +1588,11 @@ impl Display for Expression {
                     f,
                     "Fn {} ( {} ) {}",
                     i.clone(),
-                    parameters.join(", "),
+                    parameters
+                        .iter()
+                        .map(|p| p.to_string())
+                        .collect::<Vec<_>>()
+                        .join(", "),
                     body.iter()
                         .map(|b| b.to_string())
                         .collect::<Vec<_>>()
@@ -467,7 +1601,11 @@ impl Display for Expression {
                 None => write!(
                     f,
                     "Fn ( {} ) {}",
-                    parameters.join(", "),
+                    parameters
+                        .iter()
+                        .map(|p| p.to_string())
+                        .collect::<Vec<_>>()
+                        .join(", "),
                     body.iter()
                         .map(|b| b.to_string())
                         .collect::<Vec<_>>()
@@ -478,6 +1616,7 @@ impl Display for Expression {
             Expression::Call {
                 function,
                 arguments,
+                ..
             } => write!(
                 f,
                 "Call {} , {}",
@@ -489,6 +1628,9 @@ impl Display for Expression {
                     .join(", ")
             ),
 
+            Expression::NamedArgument(name, value) => write!(f, "{}: {}", name, value),
+            Expression::Spread(s) => write!(f, "...{}", s),
+            Expression::Propagate(exp) => write!(f, "{}?", exp),
             Expression::Array(elements) => write!(
                 f,
                 "[ {} ]",
@@ -503,10 +1645,11 @@ impl Display for Expression {
                 write!(f, "({} [{}])", left, index)
             }
 
-            Expression::HashMap { pairs } => {
-                let expr = pairs
+            Expression::HashMap { pairs, spreads } => {
+                let expr = spreads
                     .iter()
-                    .map(|(k, v)| format!("{} : {}", k, v))
+                    .map(|s| format!("...{}", s))
+                    .chain(pairs.iter().map(|(k, v)| format!("{} : {}", k, v)))
                     .collect::<Vec<_>>()
                     .join(", ");
 
@@ -518,6 +1661,49 @@ impl Display for Expression {
             } => {
                 write!(f, "{} of {}", attribute, identifier)
             }
+            Expression::OptionalIndex { index, left } => {
+                write!(f, "({} ?[{}])", left, index)
+            }
+            Expression::OptionalDot {
+                identifier,
+                attribute,
+            } => {
+                write!(f, "{} ?of {}", attribute, identifier)
+            }
+            Expression::Range {
+                start,
+                end,
+                step,
+                inclusive,
+            } => {
+                let op = if *inclusive { "..=" } else { ".." };
+
+                match step {
+                    Some(s) => write!(f, "{}{}{} step {}", start, op, end, s),
+                    None => write!(f, "{}{}{}", start, op, end),
+                }
+            }
+            Expression::Match { subject, arms } => write!(
+                f,
+                "Match {} {{ {} }}",
+                subject,
+                arms.iter()
+                    .map(|arm| {
+                        let body = arm
+                            .body
+                            .iter()
+                            .map(|b| b.to_string())
+                            .collect::<Vec<_>>()
+                            .join(", ");
+
+                        match &arm.guard {
+                            Some(g) => format!("{} if {} => {}", arm.pattern, g, body),
+                            None => format!("{} => {}", arm.pattern, body),
+                        }
+                    })
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
         }
     }
 }
@@ -15,4 +15,13 @@ impl Literal {
             Literal::String(s) => Object::String(s.clone()),
         }
     }
+
+    pub fn matches(&self, obj: &Object) -> bool {
+        match (self, obj) {
+            (Literal::Number(l), Object::Number(r)) => l == r,
+            (Literal::Boolean(l), Object::Boolean(r)) => l == r,
+            (Literal::String(l), Object::String(r)) => l == r,
+            _ => false,
+        }
+    }
 }
@@ -1,4 +1,6 @@
 pub mod expression;
 pub mod literal;
 pub mod parser;
+pub mod pattern;
+pub mod resolver;
 pub mod statement;
@@ -3,36 +3,45 @@ use std::collections::BTreeMap;
 use crate::{
     eval::program::Program,
     lex::{
-        lexer::Lexer,
-        token::{Token, TokenType},
+        lexer::{Lexer, KEYWORDS},
+        token::{Span, Token, TokenType},
     },
+    suggest::{closest, did_you_mean},
 };
 
 use super::{
-    expression::{Expression, Operator, Prefix},
+    expression::{Expression, MatchArm, Operator, Parameter, Prefix},
     literal::Literal,
-    statement::{Block, Identifier, Statement},
+    pattern::Pattern,
+    statement::{Block, Statement},
 };
 
 #[derive(Clone, Copy, Debug)]
 enum Precedence {
     _Int = 0,
     Lowest = 1,
-    Dot = 2,
+    NullCoalescing = 2,
     Equals = 3,
     LessGreaterOrEqual = 4,
     AndOr = 5,
-    Sum = 6,
-    Product = 7,
-    Prefix = 8,
-    Call = 9,
-    Index = 10,
+    Range = 6,
+    Sum = 7,
+    Product = 8,
+    Prefix = 9,
+    Call = 10,
+    Index = 11,
+    Dot = 12,
+    OptionalChain = 13,
 }
 
 pub struct Parser {
     lex: Lexer,
     current_token: Token,
     next_token: Token,
+    // `=>` ends a match guard, so arrow functions are not parsed inside one
+    allow_arrow_functions: bool,
+    // the last statement that looked like a misspelled keyword, with a hint for it
+    keyword_hint: Option<(Span, String)>,
 }
 
 impl Parser {
@@ -62,6 +71,7 @@ impl Parser {
     pub fn build_ast(input: &str) -> Program {
         let mut lex = Lexer::new(input);
         let mut result: Vec<Statement> = vec![];
+        let mut error = None;
 
         let first = Parser::analyze_next_token(&mut lex);
         let second = Parser::analyze_next_token(&mut lex);
@@ -70,13 +80,18 @@ impl Parser {
             lex,
             current_token: first,
             next_token: second,
+            allow_arrow_functions: true,
+            keyword_hint: None,
         };
 
         loop {
             let parsed = parser.parse_statement();
 
             match parsed {
-                Statement::Error(_) => break,
+                Statement::Error(e) => {
+                    error = Some(parser.with_keyword_hint(e));
+                    break;
+                }
                 _ => result.push(parsed),
             }
 
@@ -86,20 +101,41 @@ impl Parser {
             parser.consume_token();
         }
 
-        Program { statements: result }
+        Program {
+            statements: result,
+            error,
+        }
     }
 
     fn parse_statement(&mut self) -> Statement {
+        let is_block =
+            self.current_token.kind == TokenType::LeftBrace && !self.is_hashmap_literal();
+
         match (&self.current_token.kind, &self.next_token.kind) {
             (TokenType::Let, _) => self.parse_let_statement(),
+            (TokenType::Const, _) => self.parse_const_statement(),
             (TokenType::Return, _) => self.parse_return_statement(),
-            (TokenType::Identifier, TokenType::Asssign) => self.parse_assignment_statement(),
             (TokenType::While, _) => self.parse_while_statement(),
+            (TokenType::For, _) => self.parse_for_statement(),
+            (TokenType::Struct, _) => self.parse_struct_statement(),
+            (TokenType::Enum, _) => self.parse_enum_statement(),
+            (TokenType::Impl, _) => self.parse_impl_statement(),
+            (TokenType::Import, _) => self.parse_import_statement(),
+            (TokenType::Export, _) => self.parse_export_statement(),
+            (TokenType::Try, _) => self.parse_try_statement(),
+            (TokenType::Throw, _) => self.parse_throw_statement(),
+            (TokenType::LeftBrace, _) if is_block => self.parse_bare_block_statement(),
             _ => self.parse_expression_statement(),
         }
     }
 
     fn parse_let_statement(&mut self) -> Statement {
+        if self.next_token.kind == TokenType::LeftBracket
+            || self.next_token.kind == TokenType::LeftBrace
+        {
+            return self.parse_let_destructure_statement();
+        }
+
         if !self.expect_next_token(TokenType::Identifier) {
             return Statement::Error(format!(
                 "Expected next token to be TokenType::Identifier, got: {:?}",
@@ -116,34 +152,550 @@ impl Parser {
             ));
         }
 
-        self.consume_token();
+        self.consume_token();
+
+        let val = self.parse_expression(Precedence::Lowest);
+
+        if !self.expect_next_token(TokenType::Semicolon) {
+            return Statement::Error(format!(
+                "Expected next token to be TokenType::Semicolon, got {:?}",
+                self.next_token.kind
+            ));
+        }
+
+        Statement::Let(identifier, val)
+    }
+
+    fn parse_const_statement(&mut self) -> Statement {
+        match self.parse_let_statement() {
+            Statement::Let(identifier, val) => Statement::Const(identifier, val),
+            Statement::LetDestructure(pattern, _) => {
+                Statement::Error(format!("Constants cannot be destructured, got {}", pattern))
+            }
+            sttm => sttm,
+        }
+    }
+
+    fn parse_struct_statement(&mut self) -> Statement {
+        if !self.expect_next_token(TokenType::Identifier) {
+            return Statement::Error(format!(
+                "Expected next token to be TokenType::Identifier, got: {:?}",
+                self.next_token.kind
+            ));
+        }
+
+        let name = self.current_token.literal.clone();
+
+        if !self.expect_next_token(TokenType::LeftBrace) {
+            return Statement::Error(format!(
+                "Expected next token to be TokenType::LeftBrace, got {:?}",
+                self.next_token.kind
+            ));
+        }
+
+        let mut fields: Vec<String> = vec![];
+
+        while self.expect_next_token(TokenType::Identifier) {
+            let field = self.current_token.literal.clone();
+
+            if fields.contains(&field) {
+                return Statement::Error(format!("Duplicate field {} in struct {}", field, name));
+            }
+
+            fields.push(field);
+
+            if !self.expect_next_token(TokenType::Comma) {
+                break;
+            }
+        }
+
+        if !self.expect_next_token(TokenType::RightBrace) {
+            return Statement::Error(format!(
+                "Expected next token to be TokenType::RightBrace, got {:?}",
+                self.next_token.kind
+            ));
+        }
+
+        if self.next_token.kind == TokenType::Semicolon {
+            self.consume_token();
+        }
+
+        Statement::Struct { name, fields }
+    }
+
+    fn parse_enum_statement(&mut self) -> Statement {
+        if !self.expect_next_token(TokenType::Identifier) {
+            return Statement::Error(format!(
+                "Expected next token to be TokenType::Identifier, got: {:?}",
+                self.next_token.kind
+            ));
+        }
+
+        let name = self.current_token.literal.clone();
+
+        if !self.expect_next_token(TokenType::LeftBrace) {
+            return Statement::Error(format!(
+                "Expected next token to be TokenType::LeftBrace, got {:?}",
+                self.next_token.kind
+            ));
+        }
+
+        let mut variants: Vec<(String, Vec<String>)> = vec![];
+
+        while self.expect_next_token(TokenType::Identifier) {
+            let variant = self.current_token.literal.clone();
+
+            if variants.iter().any(|(v, _)| *v == variant) {
+                return Statement::Error(format!("Duplicate variant {} in enum {}", variant, name));
+            }
+
+            let mut fields: Vec<String> = vec![];
+
+            if self.expect_next_token(TokenType::LeftParen) {
+                while self.expect_next_token(TokenType::Identifier) {
+                    fields.push(self.current_token.literal.clone());
+
+                    if !self.expect_next_token(TokenType::Comma) {
+                        break;
+                    }
+                }
+
+                if !self.expect_next_token(TokenType::RightParen) {
+                    return Statement::Error(format!(
+                        "Expected next token to be TokenType::RightParen, got {:?}",
+                        self.next_token.kind
+                    ));
+                }
+            }
+
+            variants.push((variant, fields));
+
+            if !self.expect_next_token(TokenType::Comma) {
+                break;
+            }
+        }
+
+        if !self.expect_next_token(TokenType::RightBrace) {
+            return Statement::Error(format!(
+                "Expected next token to be TokenType::RightBrace, got {:?}",
+                self.next_token.kind
+            ));
+        }
+
+        if self.next_token.kind == TokenType::Semicolon {
+            self.consume_token();
+        }
+
+        Statement::Enum { name, variants }
+    }
+
+    fn parse_import_statement(&mut self) -> Statement {
+        let path = match &self.next_token.kind {
+            TokenType::String(s) => s.clone(),
+            kind => {
+                return Statement::Error(format!(
+                    "Expected next token to be TokenType::String, got {:?}",
+                    kind
+                ))
+            }
+        };
+        self.consume_token();
+
+        if !self.expect_next_token(TokenType::As) {
+            return Statement::Error(format!(
+                "Expected next token to be TokenType::As, got {:?}",
+                self.next_token.kind
+            ));
+        }
+
+        if !self.expect_next_token(TokenType::Identifier) {
+            return Statement::Error(format!(
+                "Expected next token to be TokenType::Identifier, got {:?}",
+                self.next_token.kind
+            ));
+        }
+
+        let alias = self.current_token.literal.clone();
+
+        if !self.expect_next_token(TokenType::Semicolon) {
+            return Statement::Error(format!(
+                "Expected next token to be TokenType::Semicolon, got {:?}",
+                self.next_token.kind
+            ));
+        }
+
+        Statement::Import { path, alias }
+    }
+
+    fn parse_export_statement(&mut self) -> Statement {
+        self.consume_token();
+
+        match self.parse_statement() {
+            Statement::Error(e) => Statement::Error(e),
+            declaration if declaration.declared_name().is_some() => {
+                Statement::Export(Box::new(declaration))
+            }
+            sttm => Statement::Error(format!("Only declarations can be exported, got {}", sttm)),
+        }
+    }
+
+    fn parse_impl_statement(&mut self) -> Statement {
+        if !self.expect_next_token(TokenType::Identifier) {
+            return Statement::Error(format!(
+                "Expected next token to be TokenType::Identifier, got: {:?}",
+                self.next_token.kind
+            ));
+        }
+
+        let name = self.current_token.literal.clone();
+
+        if !self.expect_next_token(TokenType::LeftBrace) {
+            return Statement::Error(format!(
+                "Expected next token to be TokenType::LeftBrace, got {:?}",
+                self.next_token.kind
+            ));
+        }
+
+        let mut methods = vec![];
+
+        while self.expect_next_token(TokenType::Function) {
+            match self.parse_function_expression() {
+                method @ Expression::Function {
+                    identifier: Some(_),
+                    ..
+                } => methods.push(method),
+                Expression::Error(e) => return Statement::Error(e),
+                _ => {
+                    return Statement::Error(format!("Expected a named function in impl {}", name))
+                }
+            }
+
+            if self.next_token.kind == TokenType::Semicolon {
+                self.consume_token();
+            }
+        }
+
+        if !self.expect_next_token(TokenType::RightBrace) {
+            return Statement::Error(format!(
+                "Expected next token to be TokenType::RightBrace, got {:?}",
+                self.next_token.kind
+            ));
+        }
+
+        if self.next_token.kind == TokenType::Semicolon {
+            self.consume_token();
+        }
+
+        Statement::Impl { name, methods }
+    }
+
+    fn parse_let_destructure_statement(&mut self) -> Statement {
+        self.consume_token();
+
+        let pattern = match self.parse_pattern() {
+            Ok(p) => p,
+            Err(e) => return Statement::Error(e),
+        };
+
+        if !self.expect_next_token(TokenType::Asssign) {
+            return Statement::Error(format!(
+                "Expected next token to be TokenType::Assign, got {:?}",
+                self.next_token.kind
+            ));
+        }
+
+        self.consume_token();
+
+        let val = self.parse_expression(Precedence::Lowest);
+
+        if !self.expect_next_token(TokenType::Semicolon) {
+            return Statement::Error(format!(
+                "Expected next token to be TokenType::Semicolon, got {:?}",
+                self.next_token.kind
+            ));
+        }
+
+        Statement::LetDestructure(pattern, val)
+    }
+
+    fn parse_pattern(&mut self) -> Result<Pattern, String> {
+        match &self.current_token.kind {
+            TokenType::Identifier if self.current_token.literal == "_" => Ok(Pattern::Wildcard),
+            TokenType::Identifier if self.next_token.kind == TokenType::Dot => {
+                self.parse_variant_pattern()
+            }
+            TokenType::Identifier => Ok(Pattern::Identifier(self.current_token.literal.clone())),
+            TokenType::Int(n) => Ok(Pattern::Literal(Literal::Number(*n))),
+            TokenType::String(s) => Ok(Pattern::Literal(Literal::String(s.clone()))),
+            TokenType::Boolean(b) => Ok(Pattern::Literal(Literal::Boolean(*b))),
+            TokenType::MinusSign => match self.next_token.kind {
+                TokenType::Int(n) => {
+                    self.consume_token();
+                    Ok(Pattern::Literal(Literal::Number(-n)))
+                }
+                _ => Err(format!(
+                    "Expected TokenType::Int after minus sign, got {:?}",
+                    self.next_token.kind
+                )),
+            },
+            TokenType::LeftBracket => self.parse_array_pattern(),
+            TokenType::LeftBrace => self.parse_hashmap_pattern(),
+            _ => Err(format!(
+                "Expected identifier or destructuring pattern, got {:?}",
+                self.current_token.kind
+            )),
+        }
+    }
+
+    fn parse_variant_pattern(&mut self) -> Result<Pattern, String> {
+        let enum_name = self.current_token.literal.clone();
+        self.consume_token();
+
+        if !self.expect_next_token(TokenType::Identifier) {
+            return Err(format!(
+                "Expected variant to be TokenType::Identifier, got {:?}",
+                self.next_token.kind
+            ));
+        }
+
+        let variant = self.current_token.literal.clone();
+        let mut fields = vec![];
+
+        if self.expect_next_token(TokenType::LeftParen) {
+            while self.next_token.kind != TokenType::RightParen {
+                self.consume_token();
+                fields.push(self.parse_pattern()?);
+
+                if self.next_token.kind != TokenType::RightParen
+                    && !self.expect_next_token(TokenType::Comma)
+                {
+                    return Err(format!(
+                        "Expected TokenType::Comma, got {:?}",
+                        self.next_token.kind
+                    ));
+                }
+            }
+
+            self.consume_token();
+        }
+
+        Ok(Pattern::Variant {
+            enum_name,
+            variant,
+            fields,
+        })
+    }
+
+    fn parse_array_pattern(&mut self) -> Result<Pattern, String> {
+        let mut elements: Vec<Pattern> = vec![];
+        let mut rest = None;
+
+        while self.next_token.kind != TokenType::RightBracket {
+            self.consume_token();
+
+            if self.current_token.kind == TokenType::Ellipsis {
+                if !self.expect_next_token(TokenType::Identifier) {
+                    return Err(format!(
+                        "Expected rest element to be TokenType::Identifier, got {:?}",
+                        self.next_token.kind
+                    ));
+                }
+
+                rest = Some(self.current_token.literal.clone());
+                break;
+            }
+
+            elements.push(self.parse_pattern()?);
+
+            if self.next_token.kind != TokenType::RightBracket
+                && !self.expect_next_token(TokenType::Comma)
+            {
+                return Err(format!(
+                    "Expected TokenType::Comma, got {:?}",
+                    self.next_token
+                ));
+            }
+        }
+
+        if !self.expect_next_token(TokenType::RightBracket) {
+            return Err(format!(
+                "Expected TokenType::RightBracket, got {:?}",
+                self.next_token.kind
+            ));
+        }
+
+        Ok(Pattern::Array { elements, rest })
+    }
+
+    fn parse_hashmap_pattern(&mut self) -> Result<Pattern, String> {
+        let mut pairs: Vec<(String, Pattern)> = vec![];
+
+        while self.next_token.kind != TokenType::RightBrace {
+            self.consume_token();
+
+            let key = match &self.current_token.kind {
+                TokenType::Identifier => self.current_token.literal.clone(),
+                TokenType::String(s) => s.clone(),
+                _ => {
+                    return Err(format!(
+                        "Expected hashmap pattern key, got {:?}",
+                        self.current_token.kind
+                    ))
+                }
+            };
+
+            if self.expect_next_token(TokenType::Colon) {
+                self.consume_token();
+                pairs.push((key, self.parse_pattern()?));
+            } else if self.current_token.kind == TokenType::Identifier {
+                pairs.push((key.clone(), Pattern::Identifier(key)));
+            } else {
+                return Err(format!(
+                    "Expected TokenType::Colon, got {:?}",
+                    self.next_token
+                ));
+            }
+
+            if self.next_token.kind != TokenType::RightBrace
+                && !self.expect_next_token(TokenType::Comma)
+            {
+                return Err(format!(
+                    "Expected TokenType::Comma, got {:?}",
+                    self.next_token
+                ));
+            }
+        }
+
+        if !self.expect_next_token(TokenType::RightBrace) {
+            return Err(format!(
+                "Expected TokenType::RightBrace, got {:?}",
+                self.next_token.kind
+            ));
+        }
+
+        Ok(Pattern::HashMap { pairs })
+    }
+
+    fn parse_return_statement(&mut self) -> Statement {
+        let span = self.current_token.span;
+        self.consume_token();
+
+        let return_val = self.parse_expression(Precedence::Lowest);
+
+        if self.next_token.kind == TokenType::Semicolon {
+            self.consume_token();
+        }
+
+        Statement::Return(return_val, span)
+    }
+
+    fn parse_throw_statement(&mut self) -> Statement {
+        self.consume_token();
+
+        let value = self.parse_expression(Precedence::Lowest);
+
+        if self.next_token.kind == TokenType::Semicolon {
+            self.consume_token();
+        }
+
+        Statement::Throw(value)
+    }
+
+    fn parse_try_statement(&mut self) -> Statement {
+        if !self.expect_next_token(TokenType::LeftBrace) {
+            return Statement::Error(format!(
+                "Expected next token to be TokenType::LeftBrace, got {:?}",
+                self.next_token.kind
+            ));
+        }
+
+        let body = self.parse_block_statement();
+        let mut catch = None;
+        let mut finally = None;
+
+        if self.next_token.kind == TokenType::Catch {
+            self.consume_token();
+
+            if !self.expect_next_token(TokenType::LeftParen) {
+                return Statement::Error(format!(
+                    "Expected next token to be TokenType::LeftParen, got {:?}",
+                    self.next_token.kind
+                ));
+            }
+
+            if !self.expect_next_token(TokenType::Identifier) {
+                return Statement::Error(format!(
+                    "Expected next token to be TokenType::Identifier, got {:?}",
+                    self.next_token.kind
+                ));
+            }
+
+            let name = self.current_token.literal.clone();
+
+            if !self.expect_next_token(TokenType::RightParen) {
+                return Statement::Error(format!(
+                    "Expected next token to be TokenType::RightParen, got {:?}",
+                    self.next_token.kind
+                ));
+            }
+
+            if !self.expect_next_token(TokenType::LeftBrace) {
+                return Statement::Error(format!(
+                    "Expected next token to be TokenType::LeftBrace, got {:?}",
+                    self.next_token.kind
+                ));
+            }
+
+            catch = Some((name, self.parse_block_statement()));
+        }
+
+        if self.next_token.kind == TokenType::Finally {
+            self.consume_token();
 
-        let val = self.parse_expression(Precedence::Lowest);
+            if !self.expect_next_token(TokenType::LeftBrace) {
+                return Statement::Error(format!(
+                    "Expected next token to be TokenType::LeftBrace, got {:?}",
+                    self.next_token.kind
+                ));
+            }
 
-        if !self.expect_next_token(TokenType::Semicolon) {
+            finally = Some(self.parse_block_statement());
+        }
+
+        if catch.is_none() && finally.is_none() {
             return Statement::Error(format!(
-                "Expected next token to be TokenType::Semicolon, got {:?}",
+                "Expected catch or finally after the try block, got {:?}",
                 self.next_token.kind
             ));
         }
 
-        Statement::Let(identifier, val)
-    }
-
-    fn parse_return_statement(&mut self) -> Statement {
-        self.consume_token();
-
-        let return_val = self.parse_expression(Precedence::Lowest);
-
         if self.next_token.kind == TokenType::Semicolon {
             self.consume_token();
         }
 
-        Statement::Return(return_val)
+        Statement::Try {
+            body,
+            catch,
+            finally,
+        }
+    }
+
+    fn assignment_operator(&self) -> Option<Option<Operator>> {
+        match self.next_token.kind {
+            TokenType::Asssign => Some(None),
+            TokenType::PlusAssign => Some(Some(Operator::Plus)),
+            TokenType::MinusAssign => Some(Some(Operator::Minus)),
+            TokenType::MultiplicationAssign => Some(Some(Operator::Multiply)),
+            TokenType::SlashAssign => Some(Some(Operator::Divide)),
+            _ => None,
+        }
     }
 
-    fn parse_assignment_statement(&mut self) -> Statement {
-        let identifer = self.current_token.literal.clone();
+    fn parse_assignment_statement(
+        &mut self,
+        target: Expression,
+        operator: Option<Operator>,
+    ) -> Statement {
         self.consume_token();
         self.consume_token();
 
@@ -153,7 +705,29 @@ impl Parser {
             self.consume_token();
         }
 
-        Statement::Assignment(identifer, exp)
+        match (target, operator) {
+            (Expression::Identifier(identifier, span), None) => {
+                Statement::Assignment(identifier, exp, span)
+            }
+            // `a += 1` is sugar for `a = a + 1`, reading an identifier has no side effects
+            (Expression::Identifier(identifier, span), Some(op)) => Statement::Assignment(
+                identifier.clone(),
+                Expression::Infix(
+                    op,
+                    Box::new(Expression::Identifier(identifier, span)),
+                    Box::new(exp),
+                ),
+                span,
+            ),
+            (target @ (Expression::Index { .. } | Expression::Dot { .. }), operator) => {
+                Statement::MemberAssignment {
+                    target,
+                    operator,
+                    value: exp,
+                }
+            }
+            (target, _) => Statement::Error(format!("Invalid assignment target, got {}", target)),
+        }
     }
 
     fn parse_while_statement(&mut self) -> Statement {
@@ -175,19 +749,87 @@ impl Parser {
 
         let body = self.parse_block_statement();
 
+        if self.next_token.kind == TokenType::Semicolon {
+            self.consume_token();
+        }
+
         Statement::While { condition, body }
     }
 
+    fn parse_for_statement(&mut self) -> Statement {
+        if !self.expect_next_token(TokenType::LeftParen) {
+            return Statement::Error(format!(
+                "Expected next token to be TokenType::LeftParen, got: {:?}",
+                self.next_token.kind
+            ));
+        }
+
+        self.consume_token();
+
+        let pattern = match self.parse_pattern() {
+            Ok(p) => p,
+            Err(e) => return Statement::Error(e),
+        };
+
+        if !self.expect_next_token(TokenType::In) {
+            return Statement::Error(format!(
+                "Expected next token to be TokenType::In, got {:?}",
+                self.next_token.kind
+            ));
+        }
+
+        self.consume_token();
+
+        let iterable = self.parse_expression(Precedence::Lowest);
+
+        if !self.expect_next_token(TokenType::RightParen) {
+            return Statement::Error(format!(
+                "Expected next token to be TokenType::RightParen, got {:?}",
+                self.next_token.kind
+            ));
+        }
+
+        if !self.expect_next_token(TokenType::LeftBrace) {
+            return Statement::Error(format!(
+                "Expected next token to be TokenType::LeftBrace, got {:?}",
+                self.next_token.kind
+            ));
+        }
+
+        let body = self.parse_block_statement();
+
+        if self.next_token.kind == TokenType::Semicolon {
+            self.consume_token();
+        }
+
+        Statement::For {
+            pattern,
+            iterable,
+            body,
+        }
+    }
+
     fn parse_expression(&mut self, p: Precedence) -> Expression {
+        let is_arrow_function = self.allow_arrow_functions
+            && match self.current_token.kind {
+                TokenType::Identifier => self.next_token.kind == TokenType::FatArrow,
+                TokenType::LeftParen => self.is_arrow_function(),
+                _ => false,
+            };
+
         let mut left_exp = match &self.current_token.kind {
+            _ if is_arrow_function => self.parse_arrow_function(),
             TokenType::Int(v) => Expression::Literal(Literal::Number(*v)),
-            TokenType::Identifier => Expression::Identifier(self.current_token.literal.clone()),
+            TokenType::Identifier => {
+                Expression::Identifier(self.current_token.literal.clone(), self.current_token.span)
+            }
             TokenType::String(s) => Expression::Literal(Literal::String(s.clone())),
             TokenType::Boolean(b) => Expression::Literal(Literal::Boolean(*b)),
             TokenType::BangSign => self.parse_prefix_expression(Prefix::Bang),
             TokenType::MinusSign => self.parse_prefix_expression(Prefix::Minus),
             TokenType::LeftParen => self.parse_grouped_expression(),
             TokenType::If => self.parse_if_expression(),
+            TokenType::Match => self.parse_match_expression(),
             TokenType::Function => self.parse_function_expression(),
             TokenType::LeftBracket => {
                 Expression::Array(self.parse_elements_list(TokenType::RightBracket))
@@ -222,6 +864,18 @@ impl Parser {
                 TokenType::LeftParen => self.parse_call_expression(left_exp),
                 TokenType::LeftBracket => self.parse_index_expression(left_exp),
                 TokenType::Dot => self.parse_dot_expression(left_exp),
+                TokenType::In => self.parse_infix_expression(left_exp, Operator::In),
+                TokenType::DotDot => self.parse_range_expression(left_exp, false),
+                TokenType::DotDotEq => self.parse_range_expression(left_exp, true),
+                TokenType::NullCoalescing => {
+                    self.parse_infix_expression(left_exp, Operator::NullCoalescing)
+                }
+                TokenType::OptionalDot => self.parse_optional_dot_expression(left_exp),
+                TokenType::OptionalBracket => self.parse_optional_index_expression(left_exp),
+                TokenType::Question => {
+                    self.consume_token();
+                    Expression::Propagate(Box::new(left_exp))
+                }
                 _ => left_exp,
             };
         }
@@ -230,7 +884,18 @@ impl Parser {
     }
 
     fn parse_expression_statement(&mut self) -> Statement {
-        let exp = self.parse_expression(Precedence::Lowest);
+        let exp = match self.parse_expression(Precedence::Lowest) {
+            Expression::Error(e) => Expression::Error(self.with_keyword_hint(e)),
+            exp => exp,
+        };
+
+        if let Some(hint) = self.misspelled_keyword(&exp) {
+            self.keyword_hint = Some(hint);
+        }
+
+        if let Some(operator) = self.assignment_operator() {
+            return self.parse_assignment_statement(exp, operator);
+        }
 
         if self.next_token.kind == TokenType::Semicolon {
             self.consume_token();
@@ -239,6 +904,46 @@ impl Parser {
         Statement::Expression(exp)
     }
 
+    /// Spots keywords with a typo, like `improt "a" as a`, which parse as an unknown
+    /// identifier followed by another statement. It is valid code when the name is
+    /// declared, so it only serves as a hint for a syntax error further on the line.
+    fn misspelled_keyword(&self, exp: &Expression) -> Option<(Span, String)> {
+        let (name, span) = match (exp, &self.next_token.kind) {
+            (
+                Expression::Identifier(name, span),
+                TokenType::Identifier | TokenType::Int(_) | TokenType::String(_),
+            ) => (name, span),
+            (Expression::Call { function, .. }, TokenType::LeftBrace) => match function.as_ref() {
+                Expression::Identifier(name, span) => (name, span),
+                _ => return None,
+            },
+            _ => return None,
+        };
+
+        if closest(name, KEYWORDS.iter().copied()).is_empty() {
+            return None;
+        }
+
+        Some((
+            *span,
+            format!(
+                "unknown keyword {} at {}{}",
+                name,
+                span,
+                did_you_mean(name, KEYWORDS.iter().copied())
+            ),
+        ))
+    }
+
+    fn with_keyword_hint(&mut self, error: String) -> String {
+        match self.keyword_hint.take() {
+            Some((span, hint)) if span.line == self.current_token.span.line => {
+                format!("{} ({})", error, hint)
+            }
+            _ => error,
+        }
+    }
+
     fn parse_infix_expression(&mut self, left: Expression, op: Operator) -> Expression {
         self.consume_token();
         let precedence = self.current_precedence();
@@ -249,6 +954,28 @@ impl Parser {
         Expression::Infix(op, Box::new(left), Box::new(right_expression))
     }
 
+    fn parse_range_expression(&mut self, start: Expression, inclusive: bool) -> Expression {
+        self.consume_token();
+        self.consume_token();
+
+        let end = self.parse_expression(Precedence::Range);
+
+        // `step` is only a keyword right after a range
+        let mut step = None;
+        if self.next_token.kind == TokenType::Identifier && self.next_token.literal == "step" {
+            self.consume_token();
+            self.consume_token();
+            step = Some(Box::new(self.parse_expression(Precedence::Range)));
+        }
+
+        Expression::Range {
+            start: Box::new(start),
+            end: Box::new(end),
+            step,
+            inclusive,
+        }
+    }
+
     fn parse_prefix_expression(&mut self, pr: Prefix) -> Expression {
         self.consume_token();
 
@@ -319,6 +1046,111 @@ impl Parser {
         }
     }
 
+    fn parse_match_expression(&mut self) -> Expression {
+        if !self.expect_next_token(TokenType::LeftParen) {
+            return Expression::Error(format!(
+                "expected token: TokenType::LeftParen, got: {:?}",
+                self.next_token.kind
+            ));
+        }
+
+        self.consume_token();
+
+        let subject = self.parse_expression(Precedence::Lowest);
+
+        if !self.expect_next_token(TokenType::RightParen) {
+            return Expression::Error(format!(
+                "expected token: TokenType::RightParen, got: {:?}",
+                self.next_token.kind
+            ));
+        }
+
+        if !self.expect_next_token(TokenType::LeftBrace) {
+            return Expression::Error(format!(
+                "expected token: TokenType::LeftBrace, got: {:?}",
+                self.next_token.kind
+            ));
+        }
+
+        let mut arms: Vec<MatchArm> = vec![];
+
+        while self.next_token.kind != TokenType::RightBrace {
+            self.consume_token();
+
+            let arm = match self.parse_match_arm() {
+                Ok(arm) => arm,
+                Err(e) => return Expression::Error(e),
+            };
+            arms.push(arm);
+
+            if self.next_token.kind == TokenType::Comma {
+                self.consume_token();
+            }
+        }
+
+        self.consume_token();
+
+        Expression::Match {
+            subject: Box::new(subject),
+            arms,
+        }
+    }
+
+    fn parse_match_arm(&mut self) -> Result<MatchArm, String> {
+        let mut alternatives = vec![self.parse_pattern()?];
+
+        while self.expect_next_token(TokenType::Pipe) {
+            self.consume_token();
+            alternatives.push(self.parse_pattern()?);
+        }
+
+        let pattern = match alternatives.len() {
+            1 => alternatives.pop().unwrap(),
+            _ => Pattern::Alternatives(alternatives),
+        };
+
+        let mut guard = None;
+
+        if self.expect_next_token(TokenType::If) {
+            self.consume_token();
+            self.allow_arrow_functions = false;
+            guard = Some(Box::new(self.parse_expression(Precedence::Lowest)));
+            self.allow_arrow_functions = true;
+        }
+
+        if !self.expect_next_token(TokenType::FatArrow) {
+            return Err(format!(
+                "expected token: TokenType::FatArrow, got: {:?}",
+                self.next_token.kind
+            ));
+        }
+
+        let body = if self.expect_next_token(TokenType::LeftBrace) {
+            self.parse_block_statement()
+        } else {
+            self.consume_token();
+            vec![Statement::Expression(
+                self.parse_expression(Precedence::Lowest),
+            )]
+        };
+
+        Ok(MatchArm {
+            pattern,
+            guard,
+            body,
+        })
+    }
+
+    fn parse_bare_block_statement(&mut self) -> Statement {
+        let block = self.parse_block_statement();
+
+        if self.next_token.kind == TokenType::Semicolon {
+            self.consume_token();
+        }
+
+        Statement::Block(block)
+    }
+
     fn parse_block_statement(&mut self) -> Block {
         let mut statements: Vec<Statement> = vec![];
 
@@ -404,38 +1236,170 @@ impl Parser {
         }
     }
 
-    fn parse_function_parameters(&mut self) -> Option<Vec<Identifier>> {
-        let mut identifiers: Vec<Identifier> = vec![];
+    /// Looks past the parenthesis on `current_token` for its closing one, the group is the
+    /// parameter list of an arrow function when a `=>` follows it.
+    fn is_arrow_function(&mut self) -> bool {
+        let checkpoint = self.lex.checkpoint();
+        let mut token = self.next_token.clone();
+        let mut depth = 1;
+
+        let result = loop {
+            match token.kind {
+                TokenType::LeftParen
+                | TokenType::LeftBracket
+                | TokenType::LeftBrace
+                | TokenType::OptionalBracket => depth += 1,
+                TokenType::RightParen | TokenType::RightBracket | TokenType::RightBrace => {
+                    depth -= 1
+                }
+                TokenType::Eof => break false,
+                _ => {}
+            }
+
+            token = Parser::analyze_next_token(&mut self.lex);
+
+            if depth == 0 {
+                break token.kind == TokenType::FatArrow;
+            }
+        };
+
+        self.lex.restore(checkpoint);
+        result
+    }
+
+    // A `{` starting a statement opens a block, unless it is followed by
+    // `key:` or `...` like a hashmap literal
+    fn is_hashmap_literal(&mut self) -> bool {
+        match &self.next_token.kind {
+            // `{}` stays the empty hashmap it was before blocks could stand alone
+            TokenType::Ellipsis | TokenType::RightBrace => true,
+            TokenType::Identifier | TokenType::String(_) | TokenType::Int(_) => {
+                let checkpoint = self.lex.checkpoint();
+                let token = Parser::analyze_next_token(&mut self.lex);
+                self.lex.restore(checkpoint);
+
+                token.kind == TokenType::Colon
+            }
+            _ => false,
+        }
+    }
+
+    fn parse_arrow_function(&mut self) -> Expression {
+        let parameters = if self.current_token.kind == TokenType::Identifier {
+            vec![Parameter {
+                pattern: Pattern::Identifier(self.current_token.literal.clone()),
+                default: None,
+                rest: false,
+                span: self.current_token.span,
+            }]
+        } else {
+            match self.parse_function_parameters() {
+                Some(params) => params,
+                None => {
+                    return Expression::Error(format!(
+                        "expected TokenType::RightParen, got {:?}",
+                        self.next_token.kind
+                    ))
+                }
+            }
+        };
+
+        if !self.expect_next_token(TokenType::FatArrow) {
+            return Expression::Error(format!(
+                "expected TokenType::FatArrow, got {:?}",
+                self.next_token.kind
+            ));
+        }
+
+        // a single expression body returns its value
+        let body = if self.expect_next_token(TokenType::LeftBrace) {
+            self.parse_block_statement()
+        } else {
+            self.consume_token();
+            let span = self.current_token.span;
+            vec![Statement::Return(
+                self.parse_expression(Precedence::Lowest),
+                span,
+            )]
+        };
+
+        Expression::Function {
+            identifier: None,
+            parameters,
+            body,
+        }
+    }
+
+    fn parse_function_parameters(&mut self) -> Option<Vec<Parameter>> {
+        let mut parameters: Vec<Parameter> = vec![];
 
         if self.next_token.kind == TokenType::RightParen {
             self.consume_token();
-            return Some(identifiers);
+            return Some(parameters);
         }
 
         self.consume_token();
 
-        identifiers.push(self.current_token.literal.clone());
+        parameters.push(self.parse_function_parameter()?);
 
         while self.next_token.kind == TokenType::Comma {
+            // the rest parameter has to be the last one
+            if parameters.last().is_some_and(|p| p.rest) {
+                return None;
+            }
+
             self.consume_token();
             self.consume_token();
-            identifiers.push(self.current_token.literal.clone());
+            parameters.push(self.parse_function_parameter()?);
         }
 
         if !self.expect_next_token(TokenType::RightParen) {
             return None;
         }
 
-        Some(identifiers)
+        Some(parameters)
+    }
+
+    fn parse_function_parameter(&mut self) -> Option<Parameter> {
+        if self.current_token.kind == TokenType::Ellipsis {
+            if !self.expect_next_token(TokenType::Identifier) {
+                return None;
+            }
+
+            return Some(Parameter {
+                pattern: Pattern::Identifier(self.current_token.literal.clone()),
+                default: None,
+                rest: true,
+                span: self.current_token.span,
+            });
+        }
+
+        let span = self.current_token.span;
+        let pattern = self.parse_pattern().ok()?;
+        let mut default = None;
+
+        if self.expect_next_token(TokenType::Asssign) {
+            self.consume_token();
+            default = Some(self.parse_expression(Precedence::Lowest));
+        }
+
+        Some(Parameter {
+            pattern,
+            default,
+            rest: false,
+            span,
+        })
     }
 
     fn parse_call_expression(&mut self, function: Expression) -> Expression {
         self.consume_token();
+        let span = self.current_token.span;
         let args = self.parse_elements_list(TokenType::RightParen);
 
         Expression::Call {
             function: Box::new(function),
             arguments: args,
+            span,
         }
     }
 
@@ -448,12 +1412,12 @@ impl Parser {
         }
 
         self.consume_token();
-        elements.push(self.parse_expression(Precedence::Lowest));
+        elements.push(self.parse_element());
 
         while self.next_token.kind == TokenType::Comma {
             self.consume_token();
             self.consume_token();
-            elements.push(self.parse_expression(Precedence::Lowest));
+            elements.push(self.parse_element());
         }
 
         if !self.expect_next_token(end.clone()) {
@@ -466,47 +1430,112 @@ impl Parser {
         elements
     }
 
+    fn parse_element(&mut self) -> Expression {
+        if self.current_token.kind == TokenType::Ellipsis {
+            self.consume_token();
+
+            return Expression::Spread(Box::new(self.parse_expression(Precedence::Lowest)));
+        }
+
+        // `name: value` is a named argument, only valid inside a call
+        if self.current_token.kind == TokenType::Identifier
+            && self.next_token.kind == TokenType::Colon
+        {
+            let name = self.current_token.literal.clone();
+            self.consume_token();
+            self.consume_token();
+
+            return Expression::NamedArgument(
+                name,
+                Box::new(self.parse_expression(Precedence::Lowest)),
+            );
+        }
+
+        self.parse_expression(Precedence::Lowest)
+    }
+
     fn parse_index_expression(&mut self, left_exp: Expression) -> Expression {
         self.consume_token();
 
-        let index = self.parse_expression(Precedence::Lowest);
+        let index = Expression::Array(self.parse_elements_list(TokenType::RightBracket));
+
+        Expression::Index {
+            left: Box::new(left_exp),
+            index: Box::new(index),
+        }
+    }
+
+    fn parse_dot_expression(&mut self, left: Expression) -> Expression {
+        if !self.expect_next_token(TokenType::Dot) {
+            return Expression::Error(format!(
+                "expected TokenType::Dot, got {:?}",
+                self.next_token
+            ));
+        }
+
+        if !self.expect_next_token(TokenType::Identifier) {
+            return Expression::Error(format!(
+                "Attribute is not valid, got {:?}",
+                self.next_token.kind
+            ));
+        }
 
-        Expression::Index {
-            left: Box::new(left_exp),
-            index: Box::new(index),
+        Expression::Dot {
+            identifier: Box::new(left),
+            attribute: self.current_token.literal.clone(),
         }
     }
 
-    fn parse_dot_expression(&mut self, left: Expression) -> Expression {
-        if !self.expect_next_token(TokenType::Dot) {
+    fn parse_optional_dot_expression(&mut self, left: Expression) -> Expression {
+        self.consume_token();
+
+        if !self.expect_next_token(TokenType::Identifier) {
             return Expression::Error(format!(
-                "expected TokenType::Dot, got {:?}",
-                self.next_token
+                "Attribute is not valid, got {:?}",
+                self.next_token.kind
             ));
         }
 
-        self.consume_token();
+        Expression::OptionalDot {
+            identifier: Box::new(left),
+            attribute: self.current_token.literal.clone(),
+        }
+    }
 
-        let attribute = self.parse_expression(Precedence::Lowest);
+    fn parse_optional_index_expression(&mut self, left_exp: Expression) -> Expression {
+        self.consume_token();
 
-        match attribute {
-            Expression::Identifier(name) => {
-                self.consume_token();
+        let index = Expression::Array(self.parse_elements_list(TokenType::RightBracket));
 
-                Expression::Dot {
-                    identifier: Box::new(left),
-                    attribute: name,
-                }
-            }
-            _ => Expression::Error(format!("Attribute is not valid, got {:?}", attribute)),
+        Expression::OptionalIndex {
+            left: Box::new(left_exp),
+            index: Box::new(index),
         }
     }
 
     fn parse_hashmaps_literal(&mut self) -> Expression {
         let mut btm: BTreeMap<Expression, Expression> = BTreeMap::new();
+        let mut spreads: Vec<Expression> = vec![];
 
         while self.next_token.kind != TokenType::RightBrace {
             self.consume_token();
+
+            if self.current_token.kind == TokenType::Ellipsis {
+                self.consume_token();
+                spreads.push(self.parse_expression(Precedence::Lowest));
+
+                if self.next_token.kind != TokenType::RightBrace
+                    && !self.expect_next_token(TokenType::Comma)
+                {
+                    return Expression::Error(format!(
+                        "Expected TokenType::Comma, got {:?}",
+                        self.next_token
+                    ));
+                }
+
+                continue;
+            }
+
             let key = self.parse_expression(Precedence::Lowest);
 
             if !self.expect_next_token(TokenType::Colon) {
@@ -538,7 +1567,10 @@ impl Parser {
             ));
         }
 
-        Expression::HashMap { pairs: btm }
+        Expression::HashMap {
+            pairs: btm,
+            spreads,
+        }
     }
 
     fn current_precedence(&self) -> Precedence {
@@ -556,8 +1588,15 @@ impl Parser {
             TokenType::SlashSign => Precedence::Product,
             TokenType::MultiplicationSign => Precedence::Product,
             TokenType::LeftParen => Precedence::Call,
+            TokenType::Question => Precedence::Call,
             TokenType::LeftBracket => Precedence::Index,
             TokenType::Dot => Precedence::Dot,
+            TokenType::NullCoalescing => Precedence::NullCoalescing,
+            TokenType::In => Precedence::LessGreaterOrEqual,
+            TokenType::DotDot => Precedence::Range,
+            TokenType::DotDotEq => Precedence::Range,
+            TokenType::OptionalDot => Precedence::OptionalChain,
+            TokenType::OptionalBracket => Precedence::OptionalChain,
             _ => Precedence::Lowest,
         }
     }
@@ -577,8 +1616,15 @@ impl Parser {
             TokenType::SlashSign => Precedence::Product as u8,
             TokenType::MultiplicationSign => Precedence::Product as u8,
             TokenType::LeftParen => Precedence::Call as u8,
+            TokenType::Question => Precedence::Call as u8,
             TokenType::LeftBracket => Precedence::Index as u8,
             TokenType::Dot => Precedence::Dot as u8,
+            TokenType::NullCoalescing => Precedence::NullCoalescing as u8,
+            TokenType::In => Precedence::LessGreaterOrEqual as u8,
+            TokenType::DotDot => Precedence::Range as u8,
+            TokenType::DotDotEq => Precedence::Range as u8,
+            TokenType::OptionalDot => Precedence::OptionalChain as u8,
+            TokenType::OptionalBracket => Precedence::OptionalChain as u8,
             _ => Precedence::Lowest as u8,
         }
     }
@@ -945,7 +1991,7 @@ mod test {
             }
         ";
 
-        let expected = ["while ( < Left Ident (i) , Right Number (10) ) { [Let(\"a\", Literal(Number(0))), Assignment(\"a\", Literal(Number(11)))] }"];
+        let expected = ["while ( < Left Ident (i) , Right Number (10) ) { [Let(\"a\", Literal(Number(0))), Assignment(\"a\", Literal(Number(11)), Span { line: 4, column: 17 })] }"];
         let result = Parser::build_ast(input);
 
         for (i, curr) in result.statements.iter().enumerate() {
@@ -966,4 +2012,419 @@ mod test {
             assert_eq!(curr.to_string(), expected.get(i).unwrap().to_string());
         }
     }
+
+    #[test]
+    fn parse_member_assignment() {
+        let input = "
+            a += 1;
+            arr[0] = 1;
+            map.key -= 2;
+            a.b[2].c = x;
+        ";
+
+        let expected = [
+            "= a + Left Ident (a) , Right Number (1)",
+            "= (Ident (arr) [[ Number (0) ]]) Number (1)",
+            "-= key of Ident (map) Number (2)",
+            "= c of (b of Ident (a) [[ Number (2) ]]) Ident (x)",
+        ];
+        let result = Parser::build_ast(input);
+
+        assert_eq!(result.statements.len(), expected.len());
+        for (i, curr) in result.statements.iter().enumerate() {
+            assert_eq!(curr.to_string(), expected.get(i).unwrap().to_string());
+        }
+    }
+
+    #[test]
+    fn parse_postfix_chains() {
+        let input = "
+            arr[1] + 2;
+            a.b == c.d;
+            a.b(1).c;
+        ";
+
+        let expected = [
+            "+ Left (Ident (arr) [[ Number (1) ]]) , Right Number (2)",
+            "== Left b of Ident (a) , Right d of Ident (c)",
+            "c of Call b of Ident (a) , Number (1)",
+        ];
+        let result = Parser::build_ast(input);
+
+        assert_eq!(result.statements.len(), expected.len());
+        for (i, curr) in result.statements.iter().enumerate() {
+            assert_eq!(curr.to_string(), expected.get(i).unwrap().to_string());
+        }
+    }
+
+    #[test]
+    fn parse_destructuring() {
+        let input = "
+            let [a, b, ...rest] = xs;
+            let {name, age: years} = person;
+            let [first, {x, y}] = points;
+            fn f([a, b], {c}) { }
+        ";
+
+        let expected = [
+            "Let [ a, b, ...rest ] Ident (xs)",
+            "Let { name, age: years } Ident (person)",
+            "Let [ first, { x, y } ] Ident (points)",
+            "Fn f ( [ a, b ], { c } ) ",
+        ];
+        let result = Parser::build_ast(input);
+
+        assert_eq!(result.statements.len(), expected.len());
+        for (i, curr) in result.statements.iter().enumerate() {
+            assert_eq!(curr.to_string(), expected.get(i).unwrap().to_string());
+        }
+    }
+
+    #[test]
+    fn parse_match_expression() {
+        let input = "
+            match (x) {
+                1 | -2 => \"small\",
+                [first, ...rest] if first > 0 => { first; },
+                {name} => name,
+                _ => false
+            }
+        ";
+
+        let expected = ["Match Ident (x) { 1 | -2 => String (small), [ first, ...rest ] if > Left Ident (first) , Right Number (0) => Ident (first), { name } => Ident (name), _ => Bool (false) }"];
+        let result = Parser::build_ast(input);
+
+        assert_eq!(result.statements.len(), expected.len());
+        for (i, curr) in result.statements.iter().enumerate() {
+            assert_eq!(curr.to_string(), expected.get(i).unwrap().to_string());
+        }
+    }
+
+    #[test]
+    fn parse_optional_chaining() {
+        let input = "
+            a?.b.c ?? 1 + 2;
+            a?[0] == b ?? c;
+        ";
+
+        let expected = [
+            "?? Left c of b ?of Ident (a) , Right + Left Number (1) , Right Number (2)",
+            "?? Left == Left (Ident (a) ?[[ Number (0) ]]) , Right Ident (b) , Right Ident (c)",
+        ];
+        let result = Parser::build_ast(input);
+
+        assert_eq!(result.statements.len(), expected.len());
+        for (i, curr) in result.statements.iter().enumerate() {
+            assert_eq!(curr.to_string(), expected.get(i).unwrap().to_string());
+        }
+    }
+
+    #[test]
+    fn parse_ranges() {
+        let input = "
+            0..n - 1;
+            1..=10 step 2;
+            x in 0..10;
+            for (i in 0..3) { i; }
+            xs[1..3];
+        ";
+
+        let expected = [
+            "Number (0)..- Left Ident (n) , Right Number (1)",
+            "Number (1)..=Number (10) step Number (2)",
+            "in Left Ident (x) , Right Number (0)..Number (10)",
+            "for ( i in Number (0)..Number (3) ) { Ident (i) }",
+            "(Ident (xs) [[ Number (1)..Number (3) ]])",
+        ];
+        let result = Parser::build_ast(input);
+
+        assert_eq!(result.statements.len(), expected.len());
+        for (i, curr) in result.statements.iter().enumerate() {
+            assert_eq!(curr.to_string(), expected.get(i).unwrap().to_string());
+        }
+    }
+
+    #[test]
+    fn parse_function_parameter_defaults() {
+        let input = "
+            fn f(x, y = 10, ...others) { }
+            f(1, y: 2);
+        ";
+
+        let expected = [
+            "Fn f ( x, y = Number (10), ...others ) ",
+            "Call Ident (f) , Number (1), y: Number (2)",
+        ];
+        let result = Parser::build_ast(input);
+
+        assert_eq!(result.statements.len(), expected.len());
+        for (i, curr) in result.statements.iter().enumerate() {
+            assert_eq!(curr.to_string(), expected.get(i).unwrap().to_string());
+        }
+    }
+
+    #[test]
+    fn parse_spread() {
+        let input = "
+            [...a, ...b, 4];
+            {...defaults, ...overrides, \"k\": 1};
+            f(...args);
+        ";
+
+        let expected = [
+            "[ ...Ident (a), ...Ident (b), Number (4) ]",
+            "{ ...Ident (defaults), ...Ident (overrides), String (k) : Number (1) }",
+            "Call Ident (f) , ...Ident (args)",
+        ];
+        let result = Parser::build_ast(input);
+
+        assert_eq!(result.statements.len(), expected.len());
+        for (i, curr) in result.statements.iter().enumerate() {
+            assert_eq!(curr.to_string(), expected.get(i).unwrap().to_string());
+        }
+    }
+
+    #[test]
+    fn parse_arrow_functions() {
+        let input = "
+            (x, y) => x + y;
+            x => { return x; };
+            () => 1;
+            (x, y = 2) => x;
+            (a + b) * c;
+            match (x) { n if (n) => 1, _ => 2 };
+        ";
+
+        let expected = [
+            "Fn ( x, y ) Return + Left Ident (x) , Right Ident (y)",
+            "Fn ( x ) Return Ident (x)",
+            "Fn (  ) Return Number (1)",
+            "Fn ( x, y = Number (2) ) Return Ident (x)",
+            "* Left + Left Ident (a) , Right Ident (b) , Right Ident (c)",
+            "Match Ident (x) { n if Ident (n) => Number (1), _ => Number (2) }",
+        ];
+        let result = Parser::build_ast(input);
+
+        assert_eq!(result.statements.len(), expected.len());
+        for (i, curr) in result.statements.iter().enumerate() {
+            assert_eq!(curr.to_string(), expected.get(i).unwrap().to_string());
+        }
+    }
+
+    #[test]
+    fn parse_const_statement() {
+        let input = "
+            const LIMIT = 10;
+            const NAME = \"dang\";
+        ";
+
+        let expected = ["Const LIMIT Number (10)", "Const NAME String (dang)"];
+        let result = Parser::build_ast(input);
+
+        assert_eq!(result.statements.len(), expected.len());
+        for (i, curr) in result.statements.iter().enumerate() {
+            assert_eq!(curr.to_string(), expected.get(i).unwrap().to_string());
+        }
+    }
+
+    #[test]
+    fn parse_struct_statement() {
+        let input = "
+            struct Point { x, y }
+            struct Empty {};
+            struct Config { name, debug, };
+            Point(1, y: 2);
+        ";
+
+        let expected = [
+            "Struct Point { x, y }",
+            "Struct Empty {  }",
+            "Struct Config { name, debug }",
+            "Call Ident (Point) , Number (1), y: Number (2)",
+        ];
+        let result = Parser::build_ast(input);
+
+        assert_eq!(result.statements.len(), expected.len());
+        for (i, curr) in result.statements.iter().enumerate() {
+            assert_eq!(curr.to_string(), expected.get(i).unwrap().to_string());
+        }
+
+        let duplicate = Parser::build_ast("struct Point { x, x }");
+        assert_eq!(duplicate.statements.len(), 0);
+    }
+
+    #[test]
+    fn parse_enum_statement() {
+        let input = "
+            enum Shape { Circle(r), Rect(w, h), Empty }
+            match (s) { Shape.Circle(r) => r, Shape.Rect(w, _) => w, Shape.Empty => 0 };
+        ";
+
+        let expected = [
+            "Enum Shape { Circle(r), Rect(w, h), Empty }",
+            "Match Ident (s) { Shape.Circle(r) => Ident (r), Shape.Rect(w, _) => Ident (w), Shape.Empty => Number (0) }",
+        ];
+        let result = Parser::build_ast(input);
+
+        assert_eq!(result.statements.len(), expected.len());
+        for (i, curr) in result.statements.iter().enumerate() {
+            assert_eq!(curr.to_string(), expected.get(i).unwrap().to_string());
+        }
+
+        let duplicate = Parser::build_ast("enum Shape { Empty, Empty }");
+        assert_eq!(duplicate.statements.len(), 0);
+    }
+
+    #[test]
+    fn parse_impl_statement() {
+        let input = "
+            impl Point {
+                fn norm() { self.x + self.y }
+                fn origin() { return Point(0, 0); };
+            }
+            p.norm();
+        ";
+
+        let expected = [
+            "Impl Point { Fn norm (  ) + Left x of Ident (self) , Right y of Ident (self), Fn origin (  ) Return Call Ident (Point) , Number (0), Number (0) }",
+            "Call norm of Ident (p) , ",
+        ];
+        let result = Parser::build_ast(input);
+
+        assert_eq!(result.statements.len(), expected.len());
+        for (i, curr) in result.statements.iter().enumerate() {
+            assert_eq!(curr.to_string(), expected.get(i).unwrap().to_string());
+        }
+    }
+
+    #[test]
+    fn parse_modules() {
+        let input = "
+            import \"lib/math.dang\" as math;
+            export fn add(x, y) { x + y }
+            export const PI = 3;
+            export struct Point { x, y }
+        ";
+
+        let expected = [
+            "Import \"lib/math.dang\" as math",
+            "Export Fn add ( x, y ) + Left Ident (x) , Right Ident (y)",
+            "Export Const PI Number (3)",
+            "Export Struct Point { x, y }",
+        ];
+        let result = Parser::build_ast(input);
+
+        assert_eq!(result.statements.len(), expected.len());
+        for (i, curr) in result.statements.iter().enumerate() {
+            assert_eq!(curr.to_string(), expected.get(i).unwrap().to_string());
+        }
+
+        let invalid = Parser::build_ast("export 1 + 2;");
+        assert_eq!(invalid.statements.len(), 0);
+    }
+
+    #[test]
+    fn parse_block_statements() {
+        let input = "
+            { let x = 1; x = 2; }
+            {}
+            { \"a\": 1 };
+        ";
+
+        let expected = [
+            "Block { Let x Number (1); = x Number (2) }",
+            // an empty pair of braces is still a hashmap
+            "{  }",
+            "{ String (a) : Number (1) }",
+        ];
+        let result = Parser::build_ast(input);
+
+        assert_eq!(result.statements.len(), expected.len());
+        for (i, curr) in result.statements.iter().enumerate() {
+            assert_eq!(curr.to_string(), expected.get(i).unwrap().to_string());
+        }
+    }
+
+    #[test]
+    fn parse_try_statement() {
+        let input = "
+            throw x;
+            try { risky(); } catch (e) { e.message }
+            try { 1 } finally { 2 }
+            try { 1 } catch (e) { 2 } finally { 3 };
+        ";
+
+        let expected = [
+            "Throw Ident (x)",
+            "Try { Call Ident (risky) ,  } Catch ( e ) { message of Ident (e) }",
+            "Try { Number (1) } Finally { Number (2) }",
+            "Try { Number (1) } Catch ( e ) { Number (2) } Finally { Number (3) }",
+        ];
+        let result = Parser::build_ast(input);
+
+        assert_eq!(result.statements.len(), expected.len());
+        for (i, curr) in result.statements.iter().enumerate() {
+            assert_eq!(curr.to_string(), expected.get(i).unwrap().to_string());
+        }
+
+        let invalid = Parser::build_ast("try { 1 }");
+        assert_eq!(invalid.statements.len(), 0);
+    }
+
+    #[test]
+    fn parse_propagate_expression() {
+        let input = "
+            let x = parse(s)?;
+            a[0]? + 1;
+            -f()?;
+        ";
+
+        let expected = [
+            "Let x Call Ident (parse) , Ident (s)?",
+            "+ Left (Ident (a) [[ Number (0) ]])? , Right Number (1)",
+            "- Call Ident (f) , ?",
+        ];
+        let result = Parser::build_ast(input);
+
+        assert_eq!(result.statements.len(), expected.len());
+        for (i, curr) in result.statements.iter().enumerate() {
+            assert_eq!(curr.to_string(), expected.get(i).unwrap().to_string());
+        }
+    }
+
+    #[test]
+    fn parse_misspelled_keywords() {
+        let result = Parser::build_ast("improt \"a.dang\" as a;");
+        assert_eq!(
+            result.statements[2].to_string(),
+            "error: ( parse_expression: not yet implemented, got As (unknown keyword improt at 1:1, did you mean import?) ) "
+        );
+
+        // a hint only belongs to errors on its own line
+        let result = Parser::build_ast("improt \"a.dang\"\nas;");
+        assert_eq!(
+            result.statements[2].to_string(),
+            "error: ( parse_expression: not yet implemented, got As ) "
+        );
+
+        let valid = [
+            "retrun; whlie(1); let cont = 2; cont + 1;",
+            "let a = 1;\nretrun a;",
+            "whlie (true) { 1 }",
+            "let it = 1;\nit\ny = 2;",
+        ];
+
+        for input in valid {
+            let result = Parser::build_ast(input);
+            assert!(result.error.is_none(), "{}", input);
+            assert!(
+                result
+                    .statements
+                    .iter()
+                    .all(|s| !s.to_string().starts_with("error")),
+                "{}",
+                input
+            );
+        }
+    }
 }
@@ -0,0 +1,236 @@
+use std::fmt::Display;
+
+use crate::eval::{
+    env::Environment,
+    error::ErrorKind,
+    object::{HashKey, Object},
+};
+
+use super::{literal::Literal, statement::Identifier};
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Pattern {
+    Wildcard,
+    Literal(Literal),
+    Identifier(Identifier),
+    Array {
+        elements: Vec<Pattern>,
+        rest: Option<Identifier>,
+    },
+    HashMap {
+        pairs: Vec<(String, Pattern)>,
+    },
+    Alternatives(Vec<Pattern>),
+    Variant {
+        enum_name: Identifier,
+        variant: Identifier,
+        fields: Vec<Pattern>,
+    },
+}
+
+impl Pattern {
+    /// Binds every name of the pattern into `env`, missing elements or keys become `None`.
+    pub fn bind(&self, val: Object, env: &mut Environment) -> Result<(), Object> {
+        match (self, val) {
+            (Pattern::Wildcard, _) => Ok(()),
+            (Pattern::Array { .. } | Pattern::HashMap { .. }, Object::Frozen(inner)) => {
+                self.bind(*inner, env)
+            }
+            (Pattern::Identifier(name), val) => {
+                env.set(name.clone(), val);
+                Ok(())
+            }
+            (Pattern::Array { elements, rest }, Object::Array(arr)) => {
+                for (idx, pattern) in elements.iter().enumerate() {
+                    let item = arr.get(idx).cloned().unwrap_or(Object::None);
+                    pattern.bind(item, env)?;
+                }
+
+                if let Some(r) = rest {
+                    let others = arr.iter().skip(elements.len()).cloned().collect();
+                    env.set(r.clone(), Object::Array(others));
+                }
+
+                Ok(())
+            }
+            (Pattern::HashMap { pairs: patterns }, Object::HashMap { pairs }) => {
+                for (key, pattern) in patterns {
+                    let item = pairs
+                        .get(&HashKey::new(key.clone()))
+                        .cloned()
+                        .unwrap_or(Object::None);
+                    pattern.bind(item, env)?;
+                }
+
+                Ok(())
+            }
+            (Pattern::Array { .. }, val) => Err(Object::error(
+                ErrorKind::TypeError,
+                format!("Cannot destructure {} as an array", val),
+            )),
+            (Pattern::HashMap { .. }, val) => Err(Object::error(
+                ErrorKind::TypeError,
+                format!("Cannot destructure {} as a hashmap", val),
+            )),
+            (Pattern::Literal(_) | Pattern::Alternatives(_) | Pattern::Variant { .. }, _) => {
+                Err(Object::error(
+                    ErrorKind::TypeError,
+                    format!("Cannot bind to refutable pattern {}", self),
+                ))
+            }
+        }
+    }
+
+    /// Checks `val` against the pattern, pushing the bindings of a successful match.
+    /// Unlike `bind`, arrays must have the exact length and hashmaps must have every key.
+    pub fn matches(&self, val: &Object, bindings: &mut Vec<(Identifier, Object)>) -> bool {
+        match (self, val.thawed()) {
+            (Pattern::Wildcard, _) => true,
+            (Pattern::Literal(l), _) => l.matches(val),
+            (Pattern::Identifier(name), _) => {
+                bindings.push((name.clone(), val.clone()));
+                true
+            }
+            (Pattern::Array { elements, rest }, Object::Array(arr)) => {
+                let len_matches = match rest {
+                    Some(_) => arr.len() >= elements.len(),
+                    None => arr.len() == elements.len(),
+                };
+
+                if !len_matches
+                    || !elements
+                        .iter()
+                        .zip(arr.iter())
+                        .all(|(p, v)| p.matches(v, bindings))
+                {
+                    return false;
+                }
+
+                if let Some(r) = rest {
+                    let others = arr.iter().skip(elements.len()).cloned().collect();
+                    bindings.push((r.clone(), Object::Array(others)));
+                }
+
+                true
+            }
+            (Pattern::HashMap { pairs: patterns }, Object::HashMap { pairs }) => patterns
+                .iter()
+                .all(|(key, p)| match pairs.get(&HashKey::new(key.clone())) {
+                    Some(v) => p.matches(v, bindings),
+                    None => false,
+                }),
+            (
+                Pattern::Variant {
+                    enum_name,
+                    variant,
+                    fields,
+                },
+                Object::Enum {
+                    name,
+                    variant: tag,
+                    values,
+                    ..
+                },
+            ) => {
+                enum_name == name
+                    && variant == tag
+                    && fields.len() == values.len()
+                    && fields
+                        .iter()
+                        .zip(values.iter())
+                        .all(|(p, (_, v))| p.matches(v, bindings))
+            }
+            (Pattern::Alternatives(alternatives), _) => {
+                for alt in alternatives {
+                    let mut alt_bindings = vec![];
+
+                    if alt.matches(val, &mut alt_bindings) {
+                        bindings.extend(alt_bindings);
+                        return true;
+                    }
+                }
+
+                false
+            }
+            _ => false,
+        }
+    }
+
+    /// The names the pattern binds, alternatives may bind the same name twice.
+    pub fn names(&self) -> Vec<&Identifier> {
+        match self {
+            Pattern::Wildcard | Pattern::Literal(_) => vec![],
+            Pattern::Identifier(name) => vec![name],
+            Pattern::Array { elements, rest } => elements
+                .iter()
+                .flat_map(Pattern::names)
+                .chain(rest.as_ref())
+                .collect(),
+            Pattern::HashMap { pairs } => pairs.iter().flat_map(|(_, p)| p.names()).collect(),
+            Pattern::Alternatives(patterns)
+            | Pattern::Variant {
+                fields: patterns, ..
+            } => patterns.iter().flat_map(Pattern::names).collect(),
+        }
+    }
+}
+
+impl Display for Pattern {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Pattern::Wildcard => write!(f, "_"),
+            Pattern::Literal(Literal::Number(n)) => write!(f, "{}", n),
+            Pattern::Literal(Literal::String(s)) => write!(f, "\"{}\"", s),
+            Pattern::Literal(Literal::Boolean(b)) => write!(f, "{}", b),
+            Pattern::Identifier(name) => write!(f, "{}", name),
+            Pattern::Array { elements, rest } => {
+                let mut items = elements.iter().map(|e| e.to_string()).collect::<Vec<_>>();
+
+                if let Some(r) = rest {
+                    items.push(format!("...{}", r));
+                }
+
+                write!(f, "[ {} ]", items.join(", "))
+            }
+            Pattern::HashMap { pairs } => write!(
+                f,
+                "{{ {} }}",
+                pairs
+                    .iter()
+                    .map(|(k, v)| match v {
+                        Pattern::Identifier(name) if name == k => k.clone(),
+                        _ => format!("{}: {}", k, v),
+                    })
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+            Pattern::Variant {
+                enum_name,
+                variant,
+                fields,
+            } => match fields.is_empty() {
+                true => write!(f, "{}.{}", enum_name, variant),
+                false => write!(
+                    f,
+                    "{}.{}({})",
+                    enum_name,
+                    variant,
+                    fields
+                        .iter()
+                        .map(|p| p.to_string())
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                ),
+            },
+            Pattern::Alternatives(alternatives) => write!(
+                f,
+                "{}",
+                alternatives
+                    .iter()
+                    .map(|a| a.to_string())
+                    .collect::<Vec<_>>()
+                    .join(" | ")
+            ),
+        }
+    }
+}
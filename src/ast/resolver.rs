@@ -0,0 +1,555 @@
+use std::{
+    cell::RefCell,
+    collections::{HashMap, HashSet},
+    mem,
+    rc::Rc,
+};
+
+use crate::{
+    eval::error::{ErrorKind, RuntimeError},
+    lex::{lexer::KEYWORDS, token::Span},
+    suggest::did_you_mean,
+};
+
+use super::{
+    expression::{Expression, Parameter},
+    statement::{Block, Identifier, Statement},
+};
+
+/// Names declared in one scope, mapped to whether they are constants.
+#[derive(Default)]
+struct Scope {
+    names: RefCell<HashMap<Identifier, bool>>,
+    outer: Option<Rc<Scope>>,
+}
+
+impl Scope {
+    fn enclosed(outer: &Rc<Scope>) -> Rc<Scope> {
+        Rc::new(Scope {
+            names: RefCell::default(),
+            outer: Some(Rc::clone(outer)),
+        })
+    }
+
+    fn declare(&self, name: &str, constant: bool) {
+        self.names.borrow_mut().insert(name.to_string(), constant);
+    }
+
+    /// Whether the closest declaration of `name` is a constant, `None` when it is not declared.
+    fn lookup(&self, name: &str) -> Option<bool> {
+        match self.names.borrow().get(name) {
+            Some(constant) => Some(*constant),
+            None => self.outer.as_ref().and_then(|outer| outer.lookup(name)),
+        }
+    }
+
+    fn visible_names(&self) -> Vec<Identifier> {
+        let mut names: Vec<Identifier> = self.names.borrow().keys().cloned().collect();
+
+        if let Some(outer) = &self.outer {
+            names.extend(outer.visible_names());
+        }
+
+        names
+    }
+}
+
+/// A function body with the scope it was defined in. Bodies are resolved after the
+/// code around them, like at runtime they may use names declared further down.
+struct Function<'a> {
+    scope: Rc<Scope>,
+    parameters: &'a [Parameter],
+    body: &'a Block,
+    is_method: bool,
+}
+
+/// Checks a program before it runs, so mistakes in code that rarely runs are
+/// reported up front: unknown names, assignments to undeclared variables or
+/// constants and duplicate parameters. A `return` at the top level ends the
+/// program, so it is allowed anywhere.
+pub struct Resolver<'a> {
+    scope: Rc<Scope>,
+    functions: Vec<Function<'a>>,
+    errors: Vec<RuntimeError>,
+    // the first parse error left in the tree, reported instead of everything else
+    syntax_error: Option<RuntimeError>,
+}
+
+impl<'a> Resolver<'a> {
+    /// Resolves `statements` as the top level of a program, `globals` are the names
+    /// already bound when it starts, like the builtins.
+    pub fn resolve(statements: &'a Block, globals: Vec<Identifier>) -> Vec<RuntimeError> {
+        let root = Rc::new(Scope::default());
+
+        for name in globals {
+            root.declare(&name, false);
+        }
+
+        let mut resolver = Resolver {
+            scope: root,
+            functions: vec![],
+            errors: vec![],
+            syntax_error: None,
+        };
+
+        resolver.block(statements);
+
+        while !resolver.functions.is_empty() {
+            for function in mem::take(&mut resolver.functions) {
+                resolver.function(function);
+            }
+        }
+
+        // names around a bad parse are likely wrong because of it, leave them be
+        if let Some(e) = resolver.syntax_error {
+            return vec![e];
+        }
+
+        resolver.errors.sort_by_key(|e| e.span);
+        resolver.errors
+    }
+
+    fn error(&mut self, kind: ErrorKind, message: String, span: Span) {
+        // `x += 1` reads and assigns `x` at the same place, one error is enough
+        if self.errors.iter().any(|e| e.span == Some(span)) {
+            return;
+        }
+
+        let mut error = RuntimeError::new(kind, message);
+        error.span = Some(span);
+
+        self.errors.push(error);
+    }
+
+    fn suggest(&self, name: &str) -> String {
+        let names = self.scope.visible_names();
+
+        did_you_mean(name, names.iter().map(String::as_str))
+    }
+
+    fn function(&mut self, function: Function<'a>) {
+        let scope = Scope::enclosed(&function.scope);
+
+        if function.is_method {
+            scope.declare("self", false);
+        }
+
+        let mut seen = HashSet::new();
+
+        for parameter in function.parameters {
+            for name in parameter.pattern.names() {
+                if !seen.insert(name) {
+                    self.error(
+                        ErrorKind::SyntaxError,
+                        format!("Duplicate parameter {}", name),
+                        parameter.span,
+                    );
+                }
+
+                scope.declare(name, false);
+            }
+        }
+
+        self.scope = scope;
+
+        for default in function
+            .parameters
+            .iter()
+            .filter_map(|p| p.default.as_ref())
+        {
+            self.expression(default);
+        }
+
+        self.block(function.body);
+    }
+
+    /// Named functions are hoisted, they can be called above their declaration.
+    fn block(&mut self, block: &'a Block) {
+        for sttm in block {
+            let declaration = match sttm {
+                Statement::Export(declaration) => declaration.as_ref(),
+                sttm => sttm,
+            };
+
+            if let Statement::Expression(Expression::Function {
+                identifier: Some(name),
+                ..
+            }) = declaration
+            {
+                self.scope.declare(name, false);
+            }
+        }
+
+        for sttm in block {
+            self.statement(sttm);
+        }
+    }
+
+    fn scoped_block(&mut self, block: &'a Block) {
+        let inner = Scope::enclosed(&self.scope);
+        let outer = mem::replace(&mut self.scope, inner);
+
+        self.block(block);
+        self.scope = outer;
+    }
+
+    /// `retrun x` and `whlie (x) { }` parse as an unknown name followed by another
+    /// statement, so a name starting a statement may be a misspelled keyword.
+    fn statement_head(&mut self, exp: &Expression) {
+        let (name, span) = match exp {
+            Expression::Identifier(name, span) => (name, span),
+            Expression::Call { function, .. } => match function.as_ref() {
+                Expression::Identifier(name, span) => (name, span),
+                _ => return,
+            },
+            _ => return,
+        };
+
+        if self.scope.lookup(name).is_some() {
+            return;
+        }
+
+        let names = self.scope.visible_names();
+        let candidates = names.iter().map(String::as_str);
+        let hint = did_you_mean(name, candidates.chain(KEYWORDS.iter().copied()));
+
+        self.error(
+            ErrorKind::NameError,
+            format!("identifier not found: {}{}", name, hint),
+            *span,
+        );
+    }
+
+    fn statement(&mut self, sttm: &'a Statement) {
+        match sttm {
+            Statement::Let(name, value) => {
+                self.expression(value);
+                self.scope.declare(name, false);
+            }
+            Statement::Const(name, value) => {
+                self.expression(value);
+                self.scope.declare(name, true);
+            }
+            Statement::LetDestructure(pattern, value) => {
+                self.expression(value);
+
+                for name in pattern.names() {
+                    self.scope.declare(name, false);
+                }
+            }
+            Statement::Assignment(name, value, span) => {
+                match self.scope.lookup(name) {
+                    Some(false) => {}
+                    Some(true) => self.error(
+                        ErrorKind::AssignmentError,
+                        format!("Cannot assign to constant {}", name),
+                        *span,
+                    ),
+                    None => {
+                        let hint = self.suggest(name);
+                        self.error(
+                            ErrorKind::NameError,
+                            format!("Cannot assign to undeclared variable {}{}", name, hint),
+                            *span,
+                        )
+                    }
+                }
+
+                self.expression(value);
+            }
+            Statement::MemberAssignment { target, value, .. } => {
+                self.expression(target);
+                self.expression(value);
+            }
+            Statement::Expression(exp) => {
+                self.statement_head(exp);
+                self.expression(exp);
+            }
+            Statement::Return(exp, _) | Statement::Throw(exp) => self.expression(exp),
+            // already reported by the parser
+            Statement::Error(_) => {}
+            Statement::While { condition, body } => {
+                self.expression(condition);
+                self.scoped_block(body);
+            }
+            // the pattern is bound in the scope of each iteration, next to the body
+            Statement::For {
+                pattern,
+                iterable,
+                body,
+            } => {
+                self.expression(iterable);
+
+                let inner = Scope::enclosed(&self.scope);
+                for name in pattern.names() {
+                    inner.declare(name, false);
+                }
+                let outer = mem::replace(&mut self.scope, inner);
+
+                self.block(body);
+                self.scope = outer;
+            }
+            Statement::Struct { name, .. } | Statement::Enum { name, .. } => {
+                self.scope.declare(name, false)
+            }
+            Statement::Impl { methods, .. } => {
+                for method in methods {
+                    if let Expression::Function {
+                        parameters, body, ..
+                    } = method
+                    {
+                        self.functions.push(Function {
+                            scope: Rc::clone(&self.scope),
+                            parameters,
+                            body,
+                            is_method: true,
+                        });
+                    }
+                }
+            }
+            Statement::Import { alias, .. } => self.scope.declare(alias, false),
+            Statement::Export(declaration) => self.statement(declaration),
+            Statement::Block(block) => self.scoped_block(block),
+            Statement::Try {
+                body,
+                catch,
+                finally,
+            } => {
+                self.scoped_block(body);
+
+                if let Some((name, handler)) = catch {
+                    let inner = Scope::enclosed(&self.scope);
+                    inner.declare(name, false);
+                    let outer = mem::replace(&mut self.scope, inner);
+
+                    self.block(handler);
+                    self.scope = outer;
+                }
+
+                if let Some(cleanup) = finally {
+                    self.scoped_block(cleanup);
+                }
+            }
+        }
+    }
+
+    fn expression(&mut self, exp: &'a Expression) {
+        match exp {
+            Expression::Error(e) => {
+                if self.syntax_error.is_none() {
+                    self.syntax_error = Some(RuntimeError::new(ErrorKind::SyntaxError, e.clone()));
+                }
+            }
+            Expression::Literal(_) => {}
+            Expression::Identifier(name, span) => {
+                if self.scope.lookup(name).is_none() {
+                    let hint = self.suggest(name);
+                    self.error(
+                        ErrorKind::NameError,
+                        format!("identifier not found: {}{}", name, hint),
+                        *span,
+                    );
+                }
+            }
+            Expression::Infix(_, left, right) => {
+                self.expression(left);
+                self.expression(right);
+            }
+            Expression::Prefix(_, exp)
+            | Expression::NamedArgument(_, exp)
+            | Expression::Spread(exp)
+            | Expression::Propagate(exp)
+            | Expression::Dot {
+                identifier: exp, ..
+            }
+            | Expression::OptionalDot {
+                identifier: exp, ..
+            } => self.expression(exp),
+            Expression::If {
+                condition,
+                consequence,
+                alternative,
+            } => {
+                self.expression(condition);
+                self.scoped_block(consequence);
+
+                if let Some(alternative) = alternative {
+                    self.scoped_block(alternative);
+                }
+            }
+            Expression::Function {
+                identifier,
+                parameters,
+                body,
+            } => {
+                if let Some(name) = identifier {
+                    self.scope.declare(name, false);
+                }
+
+                self.functions.push(Function {
+                    scope: Rc::clone(&self.scope),
+                    parameters,
+                    body,
+                    is_method: false,
+                });
+            }
+            Expression::Call {
+                function,
+                arguments,
+                ..
+            } => {
+                self.expression(function);
+
+                for argument in arguments {
+                    self.expression(argument);
+                }
+            }
+            Expression::Array(elements) => {
+                for element in elements {
+                    self.expression(element);
+                }
+            }
+            Expression::Index { left, index } | Expression::OptionalIndex { left, index } => {
+                self.expression(left);
+                self.expression(index);
+            }
+            Expression::HashMap { pairs, spreads } => {
+                for spread in spreads {
+                    self.expression(spread);
+                }
+
+                for (key, value) in pairs {
+                    self.expression(key);
+                    self.expression(value);
+                }
+            }
+            // every arm binds its pattern in a scope of its own
+            Expression::Match { subject, arms } => {
+                self.expression(subject);
+
+                for arm in arms {
+                    let inner = Scope::enclosed(&self.scope);
+                    for name in arm.pattern.names() {
+                        inner.declare(name, false);
+                    }
+                    let outer = mem::replace(&mut self.scope, inner);
+
+                    if let Some(guard) = &arm.guard {
+                        self.expression(guard);
+                    }
+
+                    self.block(&arm.body);
+                    self.scope = outer;
+                }
+            }
+            Expression::Range {
+                start, end, step, ..
+            } => {
+                self.expression(start);
+                self.expression(end);
+
+                if let Some(step) = step {
+                    self.expression(step);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{ast::parser::Parser, eval::env::Environment};
+
+    fn resolve(input: &str) -> Vec<String> {
+        let env = Environment::new();
+
+        Parser::build_ast(input)
+            .resolve(&env)
+            .iter()
+            .map(|e| match e.span {
+                Some(span) => format!("{} {} at {}", e.kind, e, span),
+                None => format!("{} {}", e.kind, e),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn resolve_valid_programs() {
+        let inputs = [
+            "let b = 30; let a = fn() { b = 10; return b + 10; }; a(); b + 1;",
+            "twice(2); fn twice(x) { x * 2 }",
+            "let f = fn(a) { g(a) }; let g = fn(b) { b };",
+            "let counter = 0; fn inc() { counter = counter + 1; return counter; };",
+            "struct Point { x, y }; impl Point { fn norm() { self.x + self.y } }",
+            "for ([k, v] in [[1, 2]]) { k + v; }",
+            "let [first_one, ...others] = [1, 2]; others;",
+            "match (1) { n if n > 0 => n, _ => 0 }",
+            "try { throw \"x\"; } catch (e) { e.message }",
+            "fn fact(n, acc = 1) { if (n == 0) { return acc; } fact(n - 1, acc * n) }",
+            "let xs = [1]; xs.push(len(xs)); xs[0] = 2;",
+            "let done = true; if (done) { return 1; } 2;",
+            "let it = 1; let y = 0;\nit\ny = 2;",
+        ];
+
+        for input in inputs {
+            assert_eq!(resolve(input), Vec::<String>::new(), "{}", input);
+        }
+    }
+
+    #[test]
+    fn resolve_errors() {
+        let inputs = [
+            "let total = 0;\nfn add(x) { totl = total + x; }",
+            "fn rarely() { if (false) { missing(); } }",
+            "const limit = 3; limit = 4;",
+            "count += 1;",
+            "fn add(x, y, x) { x }",
+            "let pair = fn([a, b], a) { a };",
+            "if (true) { let inner = 1; } inner;",
+            "x; let x = 1;",
+            "for ([k, v] in [[1, 2]]) { k + v; }; k;",
+            "match (1) { n if n > 0 => n, _ => 0 }; n;",
+            "let a = 1;\nretrun a;",
+            "whlie (true) { 1 }",
+        ];
+        let expected = [
+            vec![
+                "NameError Cannot assign to undeclared variable totl, did you mean total? at 2:13",
+            ],
+            vec!["NameError identifier not found: missing at 1:28"],
+            vec!["AssignmentError Cannot assign to constant limit at 1:18"],
+            vec!["NameError Cannot assign to undeclared variable count at 1:1"],
+            vec!["SyntaxError Duplicate parameter x at 1:14"],
+            vec!["SyntaxError Duplicate parameter a at 1:23"],
+            vec!["NameError identifier not found: inner at 1:30"],
+            vec!["NameError identifier not found: x at 1:1"],
+            vec!["NameError identifier not found: k at 1:38"],
+            vec!["NameError identifier not found: n at 1:40"],
+            vec!["NameError identifier not found: retrun, did you mean return? at 2:1"],
+            vec!["NameError identifier not found: whlie, did you mean while? at 1:1"],
+        ];
+
+        for (i, input) in inputs.iter().enumerate() {
+            assert_eq!(resolve(input), expected[i], "{}", input);
+        }
+
+        // a syntax error is reported alone, without the name errors it causes
+        assert_eq!(
+            resolve("fn f(...a, b) { 1 }; 2;"),
+            ["SyntaxError expected TokenType::RightParen, got Comma"]
+        );
+        assert_eq!(
+            resolve("let x = ;\ny;"),
+            ["SyntaxError Expected next token to be TokenType::Semicolon, got Identifier"]
+        );
+
+        // errors come in source order, wherever they are found
+        assert_eq!(
+            resolve("fn f() { a; }\nb;"),
+            [
+                "NameError identifier not found: a at 1:10",
+                "NameError identifier not found: b at 2:1"
+            ]
+        );
+    }
+}
@@ -1,8 +1,21 @@
 use std::fmt::Display;
 
-use crate::eval::{env::Environment, eval_block, object::Object};
+use crate::{
+    eval::{
+        env::Environment,
+        error::ErrorKind,
+        eval_block, eval_scoped_block, get_member, iterate,
+        module::import_module,
+        object::{error_value, Methods, Object},
+        resolve_member_path, set_member,
+    },
+    lex::token::Span,
+};
 
-use super::expression::Expression;
+use super::{
+    expression::{eval_infix_expression, run_tail_call, Expression, Operator},
+    pattern::Pattern,
+};
 
 pub type Block = Vec<Statement>;
 pub type Identifier = String;
@@ -10,72 +23,467 @@ pub type Identifier = String;
 #[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
 pub enum Statement {
     Let(Identifier, Expression),
-    Assignment(Identifier, Expression),
-    Return(Expression),
+    Const(Identifier, Expression),
+    LetDestructure(Pattern, Expression),
+    Assignment(Identifier, Expression, Span),
+    MemberAssignment {
+        target: Expression,
+        operator: Option<Operator>,
+        value: Expression,
+    },
+    Return(Expression, Span),
     Expression(Expression),
     Error(String),
-    While { condition: Expression, body: Block },
+    While {
+        condition: Expression,
+        body: Block,
+    },
+    For {
+        pattern: Pattern,
+        iterable: Expression,
+        body: Block,
+    },
+    Struct {
+        name: Identifier,
+        fields: Vec<Identifier>,
+    },
+    Enum {
+        name: Identifier,
+        variants: Vec<(Identifier, Vec<Identifier>)>,
+    },
+    Impl {
+        name: Identifier,
+        methods: Vec<Expression>,
+    },
+    Import {
+        path: String,
+        alias: Identifier,
+    },
+    Export(Box<Statement>),
+    Block(Block),
+    Throw(Expression),
+    Try {
+        body: Block,
+        catch: Option<(Identifier, Block)>,
+        finally: Option<Block>,
+    },
 }
 
 impl Display for Statement {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             Statement::Let(identifier, exp) => write!(f, "Let {} {}", identifier, exp),
-            Statement::Assignment(identifier, exp) => write!(f, "= {} {}", identifier, exp),
-            Statement::Return(exp) => {
+            Statement::Const(identifier, exp) => write!(f, "Const {} {}", identifier, exp),
+            Statement::LetDestructure(pattern, exp) => write!(f, "Let {} {}", pattern, exp),
+            Statement::Assignment(identifier, exp, _) => write!(f, "= {} {}", identifier, exp),
+            Statement::MemberAssignment {
+                target,
+                operator,
+                value,
+            } => match operator {
+                Some(op) => write!(f, "{}= {} {}", op, target, value),
+                None => write!(f, "= {} {}", target, value),
+            },
+            Statement::Return(exp, _) => {
                 write!(f, "Return {}", exp)
             }
             Statement::Expression(exp) => {
                 write!(f, "{}", exp)
             }
             Statement::Error(s) => write!(f, "error: ( {} )", s),
+            Statement::Struct { name, fields } => {
+                write!(f, "Struct {} {{ {} }}", name, fields.join(", "))
+            }
+            Statement::Enum { name, variants } => {
+                write!(f, "Enum {} {{ {} }}", name, display_variants(variants))
+            }
+            Statement::Import { path, alias } => write!(f, "Import \"{}\" as {}", path, alias),
+            Statement::Export(declaration) => write!(f, "Export {}", declaration),
+            Statement::Block(block) => write!(f, "Block {{ {} }}", display_block(block)),
+            Statement::Throw(exp) => write!(f, "Throw {}", exp),
+            Statement::Try {
+                body,
+                catch,
+                finally,
+            } => {
+                write!(f, "Try {{ {} }}", display_block(body))?;
+
+                if let Some((name, handler)) = catch {
+                    write!(f, " Catch ( {} ) {{ {} }}", name, display_block(handler))?;
+                }
+
+                match finally {
+                    Some(cleanup) => write!(f, " Finally {{ {} }}", display_block(cleanup)),
+                    None => Ok(()),
+                }
+            }
+            Statement::Impl { name, methods } => write!(
+                f,
+                "Impl {} {{ {} }}",
+                name,
+                methods
+                    .iter()
+                    .map(|m| m.to_string())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
             Statement::While { condition, body } => {
                 write!(f, "while ( {} ) {{ {:?} }}", condition, body)
             }
+            Statement::For {
+                pattern,
+                iterable,
+                body,
+            } => {
+                write!(
+                    f,
+                    "for ( {} in {} ) {{ {} }}",
+                    pattern,
+                    iterable,
+                    body.iter()
+                        .map(|b| b.to_string())
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                )
+            }
         }
     }
 }
 
+/// Formats enum variants as `Circle(r), Rect(w, h), Empty`.
+pub fn display_variants(variants: &[(Identifier, Vec<Identifier>)]) -> String {
+    variants
+        .iter()
+        .map(|(variant, fields)| match fields.is_empty() {
+            true => variant.clone(),
+            false => format!("{}({})", variant, fields.join(", ")),
+        })
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+fn display_block(block: &Block) -> String {
+    block
+        .iter()
+        .map(|sttm| sttm.to_string())
+        .collect::<Vec<_>>()
+        .join("; ")
+}
+
+// The `Error` structs made by `error_value`, throwing one again keeps its kind
+fn is_error_value(obj: &Object) -> bool {
+    match obj {
+        Object::Struct { name, fields, .. } => {
+            name == "Error"
+                && fields
+                    .iter()
+                    .map(|(k, _)| k.as_str())
+                    .eq(["kind", "message", "value", "cause"])
+        }
+        _ => false,
+    }
+}
+
+// A `return f(...)` inside `try` has to make the call before leaving it, otherwise
+// errors raised by the call would escape the `catch`
+fn settle(result: Object) -> Object {
+    match result {
+        Object::Return(r) => match run_tail_call(*r) {
+            err if err.is_abrupt() => err,
+            r => Object::Return(Box::new(r)),
+        },
+        result => result,
+    }
+}
+
 impl Statement {
+    /// The name bound by a declaration, the ones that can be exported.
+    pub fn declared_name(&self) -> Option<&Identifier> {
+        match self {
+            Statement::Let(name, _)
+            | Statement::Const(name, _)
+            | Statement::Struct { name, .. }
+            | Statement::Enum { name, .. }
+            | Statement::Expression(Expression::Function {
+                identifier: Some(name),
+                ..
+            }) => Some(name),
+            _ => None,
+        }
+    }
+
     pub fn eval(&self, env: &mut Environment) -> Object {
         match self {
             Statement::Expression(exp) => exp.eval(env),
-            Statement::Return(r) => {
-                let result = r.eval(env);
+            Statement::Return(r, _) => {
+                let result = r.eval_tail_call(env);
+                if result.is_abrupt() {
+                    return result;
+                }
+
                 Object::Return(Box::new(result))
             }
             Statement::Let(ident, exp) => {
+                if env.is_const(ident) {
+                    return Object::error(
+                        ErrorKind::AssignmentError,
+                        format!("Cannot redeclare constant {}", ident),
+                    );
+                }
+
                 let val = exp.eval(env);
+                if val.is_abrupt() {
+                    return val;
+                }
+
                 env.set(ident.clone(), val.clone());
 
                 val
             }
-            Statement::Assignment(ident, exp) => {
+            Statement::Const(ident, exp) => {
+                if env.is_const(ident) {
+                    return Object::error(
+                        ErrorKind::AssignmentError,
+                        format!("Cannot redeclare constant {}", ident),
+                    );
+                }
+
                 let val = exp.eval(env);
+                if val.is_abrupt() {
+                    return val;
+                }
+
+                env.set_const(ident.clone(), val.clone());
+
+                val
+            }
+            Statement::LetDestructure(pattern, exp) => {
+                let val = exp.eval(env);
+                if val.is_abrupt() {
+                    return val;
+                }
+
+                match pattern.bind(val.clone(), env) {
+                    Ok(()) => val,
+                    Err(e) => e,
+                }
+            }
+            Statement::Assignment(ident, exp, _) => {
+                let val = exp.eval(env);
+                if val.is_abrupt() {
+                    return val;
+                }
+
+                match env.assign(ident.clone(), val.clone()) {
+                    Ok(()) => val,
+                    Err(e) => e,
+                }
+            }
+            Statement::MemberAssignment {
+                target,
+                operator,
+                value,
+            } => {
+                let (root, keys) = match resolve_member_path(target, env) {
+                    Ok(path) => path,
+                    Err(e) => return e,
+                };
 
-                match env.get(ident.clone()) {
-                    Some(_) => {
-                        env.set(ident.clone(), val.clone());
-                        val
+                let val = value.eval(env);
+                if val.is_abrupt() {
+                    return val;
+                }
+
+                let current = match env.get(root.clone()) {
+                    Some(obj) => obj,
+                    None => return env.not_found(&root),
+                };
+
+                let val = match operator {
+                    Some(op) => {
+                        let mut old = current.clone();
+                        for key in keys.iter() {
+                            old = get_member(&old, key);
+                            if old.is_error() {
+                                return old;
+                            }
+                        }
+
+                        eval_infix_expression(op, old, val)
                     }
-                    None => Object::Error(format!("Identifier not found: {}", ident)),
+                    None => val,
+                };
+                if val.is_abrupt() {
+                    return val;
+                }
+
+                match set_member(current, &keys, val.clone()) {
+                    Ok(updated) => match env.assign(root, updated) {
+                        Ok(()) => val,
+                        Err(e) => e,
+                    },
+                    Err(e) => e,
                 }
             }
-            Statement::Error(s) => Object::Error(s.clone()),
+            Statement::Error(s) => Object::error(ErrorKind::SyntaxError, s.clone()),
+            Statement::Struct { name, fields } => {
+                let struct_type = Object::StructType {
+                    name: name.clone(),
+                    fields: fields.clone(),
+                    methods: Methods::default(),
+                };
+                env.set(name.clone(), struct_type.clone());
+
+                struct_type
+            }
+            Statement::Enum { name, variants } => {
+                let enum_type = Object::EnumType {
+                    name: name.clone(),
+                    variants: variants.clone(),
+                    methods: Methods::default(),
+                };
+                env.set(name.clone(), enum_type.clone());
+
+                enum_type
+            }
+            Statement::Import { path, alias } => match import_module(path, env) {
+                Ok(module) => {
+                    env.set(alias.clone(), module.clone());
+                    module
+                }
+                Err(e) => e,
+            },
+            Statement::Export(declaration) => declaration.eval(env),
+            Statement::Impl { name, methods } => {
+                let table = match env.get(name.clone()) {
+                    Some(Object::StructType { methods, .. } | Object::EnumType { methods, .. }) => {
+                        methods
+                    }
+                    Some(obj) => {
+                        return Object::error(
+                            ErrorKind::TypeError,
+                            format!("Cannot implement methods for {}", obj),
+                        )
+                    }
+                    None => return env.not_found(name),
+                };
+
+                for method in methods {
+                    if let Expression::Function {
+                        identifier: Some(method_name),
+                        parameters,
+                        body,
+                    } = method
+                    {
+                        let function = Object::Function {
+                            name: Some(format!("{}.{}", name, method_name)),
+                            parameters: parameters.clone(),
+                            body: body.clone(),
+                            env: env.clone(),
+                        };
+
+                        table.borrow_mut().insert(method_name.clone(), function);
+                    }
+                }
+
+                Object::None
+            }
             Statement::While { condition, body } => {
                 loop {
                     let assertion = condition.eval(env);
 
                     match assertion {
                         Object::Boolean(true) => {
-                            eval_block(body, env);
+                            let result = eval_scoped_block(body, env);
+
+                            if result.is_abrupt() {
+                                return result;
+                            }
                         }
+                        err if err.is_abrupt() => return err,
                         _ => break,
                     }
                 }
 
                 Object::None
             }
+            Statement::Block(block) => eval_scoped_block(block, env),
+            Statement::For {
+                pattern,
+                iterable,
+                body,
+            } => {
+                let items = match iterate(iterable.eval(env)) {
+                    Ok(items) => items,
+                    Err(e) => return e,
+                };
+
+                for item in items {
+                    // every iteration binds the pattern in a scope of its own
+                    let mut scope = Environment::enclosed(env);
+
+                    if let Err(e) = pattern.bind(item, &mut scope) {
+                        return e;
+                    }
+
+                    let result = eval_block(body, &mut scope);
+
+                    if let Object::Return(_) = result {
+                        return result;
+                    }
+
+                    if result.is_error() {
+                        return result;
+                    }
+                }
+
+                Object::None
+            }
+            Statement::Throw(exp) => match exp.eval(env) {
+                err if err.is_abrupt() => err,
+                err @ Object::Struct { .. } if is_error_value(&err) => {
+                    Object::Thrown(Box::new(err))
+                }
+                Object::String(message) => Object::Thrown(Box::new(error_value(
+                    "Error",
+                    message.clone(),
+                    Object::String(message),
+                    Object::None,
+                ))),
+                value => Object::Thrown(Box::new(error_value(
+                    "Error",
+                    value.to_string(),
+                    value,
+                    Object::None,
+                ))),
+            },
+            Statement::Try {
+                body,
+                catch,
+                finally,
+            } => {
+                let mut result = settle(eval_scoped_block(body, env));
+
+                if let (true, Some((name, handler))) = (result.is_error(), catch) {
+                    env.calls.borrow_mut().trace = None;
+
+                    let mut scope = Environment::enclosed(env);
+                    scope.set(name.clone(), result.into_caught());
+
+                    result = settle(eval_block(handler, &mut scope));
+                }
+
+                if let Some(cleanup) = finally {
+                    let after = eval_scoped_block(cleanup, env);
+
+                    // a `return` or an error in `finally` replaces the outcome of the try
+                    if after.is_error() || matches!(after, Object::Return(_)) {
+                        return after;
+                    }
+                }
+
+                result
+            }
         }
     }
 }
@@ -1,10 +1,156 @@
-use crate::eval::object::Object;
-use std::collections::HashMap;
+use crate::{eval::object::Object, lex::token::Span, suggest::did_you_mean};
+use std::{
+    cell::RefCell,
+    collections::{HashMap, HashSet},
+    fmt::{Debug, Display},
+    path::PathBuf,
+    rc::Rc,
+};
 
-use super::builtin_functions;
+use super::{builtin_functions, error::ErrorKind, module::Modules};
 
+/// How deep calls may nest before a run is stopped with an error.
+pub const DEFAULT_MAX_DEPTH: usize = 1000;
+
+/// How many bytes of stack calls may use before a run is stopped with an error,
+/// half of the 2 MiB Rust gives a spawned thread. Every dang call takes a few
+/// evaluator frames, so this usually runs out before `DEFAULT_MAX_DEPTH` does.
+pub const DEFAULT_STACK_LIMIT: usize = 1024 * 1024;
+
+// Deep recursion makes for long traces, only both ends of them are printed
+const TRACE_EDGE: usize = 10;
+
+/// Calls in progress, shared by every scope of a run.
+#[derive(Debug)]
+pub struct CallStack {
+    pub frames: Vec<Frame>,
+    pub max_depth: usize,
+    pub stack_limit: usize,
+    // where the stack was when the outermost call started
+    stack_base: usize,
+    /// Whether frames keep the arguments of their call.
+    pub verbose: bool,
+    /// The frames an error was raised in, kept while the error unwinds.
+    pub trace: Option<Trace>,
+}
+
+impl Default for CallStack {
+    fn default() -> Self {
+        Self {
+            frames: vec![],
+            max_depth: DEFAULT_MAX_DEPTH,
+            stack_limit: DEFAULT_STACK_LIMIT,
+            stack_base: 0,
+            verbose: false,
+            trace: None,
+        }
+    }
+}
+
+impl CallStack {
+    /// Whether another call would nest too deep, by the number of calls in progress
+    /// or by the stack they use. Overflowing the stack would abort the process.
+    pub fn is_full(&mut self) -> bool {
+        let marker = 0u8;
+        let here = &marker as *const u8 as usize;
+
+        if self.frames.is_empty() {
+            self.stack_base = here;
+        }
+
+        self.frames.len() >= self.max_depth || self.stack_base.abs_diff(here) > self.stack_limit
+    }
+
+    /// Snapshots the stack for an error leaving the innermost frame, outer frames
+    /// the same error unwinds through keep the deeper trace.
+    pub fn record(&mut self, error: &Object) {
+        let error = error.to_string();
+
+        if let Some(trace) = &self.trace {
+            if trace.error == error && trace.frames.len() >= self.frames.len() {
+                return;
+            }
+        }
+
+        self.trace = Some(Trace {
+            error,
+            frames: self.frames.clone(),
+        });
+    }
+}
+
+/// A call of a user function, `args` are only kept for verbose traces.
+#[derive(Debug, Clone)]
+pub struct Frame {
+    pub name: Option<String>,
+    pub span: Span,
+    pub args: Option<Vec<String>>,
+}
+
+impl Display for Frame {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.name.as_deref().unwrap_or("<anonymous>"))?;
+
+        // calls made by builtins, like the callback of `map`, have no call site
+        if self.span != Span::default() {
+            write!(f, " ({})", self.span)?;
+        }
+
+        match &self.args {
+            Some(args) => write!(f, " with ({})", args.join(", ")),
+            None => Ok(()),
+        }
+    }
+}
+
+/// The stack at the point an error was raised, outermost call first.
+#[derive(Debug, Clone)]
+pub struct Trace {
+    pub error: String,
+    pub frames: Vec<Frame>,
+}
+
+impl Display for Trace {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let frames = self.frames.iter().rev().collect::<Vec<_>>();
+
+        for (i, frame) in frames.iter().enumerate() {
+            if frames.len() > 2 * TRACE_EDGE && i == TRACE_EDGE {
+                writeln!(f, "    ... {} more", frames.len() - 2 * TRACE_EDGE)?;
+            }
+
+            if frames.len() > 2 * TRACE_EDGE && (TRACE_EDGE..frames.len() - TRACE_EDGE).contains(&i)
+            {
+                continue;
+            }
+
+            writeln!(f, "    at {}", frame)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// What a scope knows about its names besides their values.
+#[derive(Debug, Default)]
+pub struct Bindings {
+    /// Names declared with `const`.
+    pub constants: HashSet<String>,
+    /// Names updated by an assignment since they were bound.
+    pub assigned: HashSet<String>,
+}
+
+/// A scope of bindings. Cloning an environment shares its store, which is how
+/// closures keep seeing the scope they were defined in.
+#[derive(Clone)]
 pub struct Environment {
-    pub store: HashMap<String, Object>,
+    pub store: Rc<RefCell<HashMap<String, Object>>>,
+    pub bindings: Rc<RefCell<Bindings>>,
+    /// The file being run, imports resolve relative to it.
+    pub file: Option<Rc<PathBuf>>,
+    pub modules: Rc<RefCell<Modules>>,
+    pub calls: Rc<RefCell<CallStack>>,
+    pub outer: Option<Box<Environment>>,
 }
 
 impl Default for Environment {
@@ -13,16 +159,152 @@ impl Default for Environment {
     }
 }
 
+// Functions capture their environment, printing it would recurse forever
+impl Debug for Environment {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Environment")
+    }
+}
+
 impl Environment {
     pub fn new() -> Self {
         builtin_functions()
     }
 
+    pub fn enclosed(outer: &Environment) -> Self {
+        Self {
+            store: Rc::new(RefCell::new(HashMap::new())),
+            bindings: Rc::default(),
+            file: outer.file.clone(),
+            modules: Rc::clone(&outer.modules),
+            calls: Rc::clone(&outer.calls),
+            outer: Some(Box::new(outer.clone())),
+        }
+    }
+
+    /// Limits how deep calls may nest, deeper calls fail with
+    /// "maximum recursion depth exceeded".
+    pub fn set_max_depth(&mut self, max_depth: usize) {
+        self.calls.borrow_mut().max_depth = max_depth;
+    }
+
+    /// Limits how many bytes of stack calls may use, it has to stay below the stack
+    /// size of the thread the program runs on.
+    pub fn set_stack_limit(&mut self, stack_limit: usize) {
+        self.calls.borrow_mut().stack_limit = stack_limit;
+    }
+
+    /// Keeps the arguments of every call in stack traces.
+    pub fn set_verbose_traces(&mut self, verbose: bool) {
+        self.calls.borrow_mut().verbose = verbose;
+    }
+
+    /// The stack trace of `error`, if it was raised inside a function.
+    pub fn take_trace(&mut self, error: &Object) -> Option<Trace> {
+        let trace = self.calls.borrow_mut().trace.take()?;
+
+        match trace.error == error.to_string() {
+            true => Some(trace),
+            false => None,
+        }
+    }
+
     pub fn get(&mut self, name: String) -> Option<Object> {
-        self.store.get(name.as_str()).cloned()
+        match self.store.borrow().get(name.as_str()) {
+            Some(obj) => Some(obj.clone()),
+            None => match &mut self.outer {
+                Some(outer) => outer.get(name),
+                None => None,
+            },
+        }
+    }
+
+    /// Every name visible from this scope, used to suggest one for a mistyped name.
+    pub fn names(&self) -> Vec<String> {
+        let mut names: Vec<String> = self.store.borrow().keys().cloned().collect();
+
+        if let Some(outer) = &self.outer {
+            names.extend(outer.names());
+        }
+
+        names
+    }
+
+    /// Whether `name` is bound in this scope or an outer one.
+    pub fn contains(&self, name: &str) -> bool {
+        self.store.borrow().contains_key(name)
+            || self
+                .outer
+                .as_ref()
+                .is_some_and(|outer| outer.contains(name))
+    }
+
+    /// A hint naming the closest names in scope, for an error about `name`.
+    pub fn suggest(&self, name: &str) -> String {
+        let names = self.names();
+
+        did_you_mean(name, names.iter().map(String::as_str))
+    }
+
+    /// The error for reading `name` when it is not bound.
+    pub fn not_found(&self, name: &str) -> Object {
+        Object::error(
+            ErrorKind::NameError,
+            format!("identifier not found: {}{}", name, self.suggest(name)),
+        )
     }
 
     pub fn set(&mut self, name: String, val: Object) {
-        self.store.insert(name, val);
+        self.store.borrow_mut().insert(name, val);
+    }
+
+    pub fn set_const(&mut self, name: String, val: Object) {
+        self.bindings.borrow_mut().constants.insert(name.clone());
+        self.set(name, val);
+    }
+
+    /// Whether `name` of the current scope was assigned to since it was bound.
+    pub fn is_assigned(&self, name: &str) -> bool {
+        self.bindings.borrow().assigned.contains(name)
+    }
+
+    /// Whether `name` is a constant of the current scope.
+    pub fn is_const(&self, name: &str) -> bool {
+        self.bindings.borrow().constants.contains(name)
+    }
+
+    /// Fails the way `assign` would for `name`, without assigning anything.
+    pub fn check_assign(&self, name: &str) -> Result<(), Object> {
+        if !self.contains(name) {
+            return Err(self.not_found(name));
+        }
+
+        let bound_here = self.store.borrow().contains_key(name);
+
+        match (bound_here, &self.outer) {
+            (true, _) if self.is_const(name) => Err(Object::error(
+                ErrorKind::AssignmentError,
+                format!("Cannot assign to constant {}", name),
+            )),
+            (false, Some(outer)) => outer.check_assign(name),
+            _ => Ok(()),
+        }
+    }
+
+    /// Updates the closest existing binding, constants cannot be updated.
+    pub fn assign(&mut self, name: String, val: Object) -> Result<(), Object> {
+        self.check_assign(&name)?;
+        self.update(name, val);
+
+        Ok(())
+    }
+
+    fn update(&mut self, name: String, val: Object) {
+        if self.store.borrow().contains_key(name.as_str()) {
+            self.bindings.borrow_mut().assigned.insert(name.clone());
+            self.set(name, val);
+        } else if let Some(outer) = &mut self.outer {
+            outer.update(name, val);
+        }
     }
 }
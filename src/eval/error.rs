@@ -0,0 +1,67 @@
+use std::fmt::Display;
+
+use crate::lex::token::Span;
+
+/// What went wrong, scripts read it as `e.kind` once the error is caught.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ErrorKind {
+    /// An operation got a value of the wrong type.
+    TypeError,
+    /// An identifier that is not bound.
+    NameError,
+    /// A field, export, function or variant that does not exist.
+    AttributeError,
+    /// An index of the wrong shape or out of bounds.
+    IndexError,
+    /// A call or constructor given the wrong arguments.
+    ArityError,
+    DivisionByZero,
+    /// A value of the right type that still cannot be used, like a zero range step.
+    ValueError,
+    /// Assigning to a constant or to a frozen value.
+    AssignmentError,
+    /// A `match` none of the arms matched.
+    MatchError,
+    RecursionError,
+    ImportError,
+    /// Code the parser could not make sense of.
+    SyntaxError,
+}
+
+impl Display for ErrorKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct RuntimeError {
+    pub kind: ErrorKind,
+    pub message: String,
+    /// The call site closest to where the error was raised, if it happened in a call.
+    pub span: Option<Span>,
+    /// The error this one was raised because of, like a failure inside an imported module.
+    pub cause: Option<Box<RuntimeError>>,
+}
+
+impl RuntimeError {
+    pub fn new(kind: ErrorKind, message: String) -> Self {
+        Self {
+            kind,
+            message,
+            span: None,
+            cause: None,
+        }
+    }
+}
+
+impl Display for RuntimeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.message)?;
+
+        match &self.cause {
+            Some(cause) => write!(f, ": {}", cause),
+            None => Ok(()),
+        }
+    }
+}
@@ -0,0 +1,329 @@
+use std::collections::HashMap;
+
+use crate::ast::expression::{call_function, is_same};
+
+use super::{
+    error::ErrorKind,
+    object::{CustomHash, HashKey, Object},
+};
+
+const STRING_METHODS: &[&str] = &[
+    "len",
+    "upper",
+    "lower",
+    "trim",
+    "chars",
+    "split",
+    "contains",
+    "starts_with",
+    "ends_with",
+];
+const ARRAY_METHODS: &[&str] = &[
+    "len", "first", "last", "push", "pop", "reverse", "contains", "join", "map", "filter", "reduce",
+];
+const HASHMAP_METHODS: &[&str] = &["len", "keys", "values", "contains_key", "remove"];
+// the only methods that change their receiver, the others never store it back
+const MUTATING_METHODS: &[&str] = &["push", "pop", "remove"];
+
+type StringMethod = fn(&str, Vec<Object>) -> Object;
+type ArrayMethod = fn(&mut Vec<Object>, Vec<Object>) -> Object;
+type HashMapMethod = fn(&mut HashMap<HashKey, Object>, Vec<Object>) -> Object;
+
+/// A method of a builtin type, `"abc".len()` or `xs.push(4)`.
+pub enum BuiltinMethod {
+    String(StringMethod),
+    Array(ArrayMethod),
+    HashMap(HashMapMethod),
+}
+
+impl BuiltinMethod {
+    /// Finds `name` in the method table of the receiver's type.
+    pub fn lookup(receiver: &Object, name: &str) -> Option<BuiltinMethod> {
+        match receiver.thawed() {
+            Object::String(_) => string_method(name).map(BuiltinMethod::String),
+            Object::Array(_) => array_method(name).map(BuiltinMethod::Array),
+            Object::HashMap { .. } => hashmap_method(name).map(BuiltinMethod::HashMap),
+            _ => None,
+        }
+    }
+
+    /// The names in the method table of the receiver's type.
+    pub fn names(receiver: &Object) -> &'static [&'static str] {
+        match receiver.thawed() {
+            Object::String(_) => STRING_METHODS,
+            Object::Array(_) => ARRAY_METHODS,
+            Object::HashMap { .. } => HASHMAP_METHODS,
+            _ => &[],
+        }
+    }
+
+    /// Whether the method named `name` changes its receiver, like `push`.
+    pub fn mutates(name: &str) -> bool {
+        MUTATING_METHODS.contains(&name)
+    }
+
+    /// Runs the method, the ones like `push` update `receiver` in place.
+    pub fn call(&self, name: &str, receiver: &mut Object, args: Vec<Object>) -> Object {
+        if let Object::Frozen(inner) = receiver {
+            if BuiltinMethod::mutates(name) {
+                return Object::error(
+                    ErrorKind::AssignmentError,
+                    format!("Cannot call {} on {}, it is frozen", name, inner),
+                );
+            }
+
+            return self.call(name, &mut inner.as_ref().clone(), args);
+        }
+
+        match (self, receiver) {
+            (BuiltinMethod::String(method), Object::String(s)) => method(s, args),
+            (BuiltinMethod::Array(method), Object::Array(arr)) => method(arr, args),
+            (BuiltinMethod::HashMap(method), Object::HashMap { pairs }) => method(pairs, args),
+            (_, receiver) => Object::error(
+                ErrorKind::AttributeError,
+                format!("{} has no method {}", receiver, name),
+            ),
+        }
+    }
+}
+
+fn expect_args(name: &str, args: &[Object], expected: usize) -> Result<(), Object> {
+    if args.len() != expected {
+        return Err(Object::error(
+            ErrorKind::ArityError,
+            format!(
+                "'{}' expects {} argument(s), got {}",
+                name,
+                expected,
+                args.len()
+            ),
+        ));
+    }
+
+    Ok(())
+}
+
+fn expect_string(name: &str, args: &[Object]) -> Result<String, Object> {
+    expect_args(name, args, 1)?;
+
+    match &args[0] {
+        Object::String(s) => Ok(s.clone()),
+        obj => Err(Object::error(
+            ErrorKind::TypeError,
+            format!("'{}' expects a string, got {}", name, obj),
+        )),
+    }
+}
+
+fn string_method(name: &str) -> Option<StringMethod> {
+    let method: StringMethod = match name {
+        "len" => |s, args| match expect_args("len", &args, 0) {
+            Ok(()) => Object::Number(s.len() as i64),
+            Err(e) => e,
+        },
+        "upper" => |s, args| match expect_args("upper", &args, 0) {
+            Ok(()) => Object::String(s.to_uppercase()),
+            Err(e) => e,
+        },
+        "lower" => |s, args| match expect_args("lower", &args, 0) {
+            Ok(()) => Object::String(s.to_lowercase()),
+            Err(e) => e,
+        },
+        "trim" => |s, args| match expect_args("trim", &args, 0) {
+            Ok(()) => Object::String(s.trim().to_string()),
+            Err(e) => e,
+        },
+        "chars" => |s, args| match expect_args("chars", &args, 0) {
+            Ok(()) => Object::Array(s.chars().map(|c| Object::String(c.to_string())).collect()),
+            Err(e) => e,
+        },
+        "split" => |s, args| match expect_string("split", &args) {
+            Ok(sep) => Object::Array(
+                s.split(sep.as_str())
+                    .map(|part| Object::String(part.to_string()))
+                    .collect(),
+            ),
+            Err(e) => e,
+        },
+        "contains" => |s, args| match expect_string("contains", &args) {
+            Ok(sub) => Object::Boolean(s.contains(sub.as_str())),
+            Err(e) => e,
+        },
+        "starts_with" => |s, args| match expect_string("starts_with", &args) {
+            Ok(prefix) => Object::Boolean(s.starts_with(prefix.as_str())),
+            Err(e) => e,
+        },
+        "ends_with" => |s, args| match expect_string("ends_with", &args) {
+            Ok(suffix) => Object::Boolean(s.ends_with(suffix.as_str())),
+            Err(e) => e,
+        },
+        _ => return None,
+    };
+
+    Some(method)
+}
+
+fn array_method(name: &str) -> Option<ArrayMethod> {
+    let method: ArrayMethod = match name {
+        "len" => |arr, args| match expect_args("len", &args, 0) {
+            Ok(()) => Object::Number(arr.len() as i64),
+            Err(e) => e,
+        },
+        "first" => |arr, args| match expect_args("first", &args, 0) {
+            Ok(()) => arr.first().cloned().unwrap_or(Object::None),
+            Err(e) => e,
+        },
+        "last" => |arr, args| match expect_args("last", &args, 0) {
+            Ok(()) => arr.last().cloned().unwrap_or(Object::None),
+            Err(e) => e,
+        },
+        "push" => |arr, args| match expect_args("push", &args, 1) {
+            Ok(()) => {
+                arr.extend(args);
+                Object::None
+            }
+            Err(e) => e,
+        },
+        "pop" => |arr, args| match expect_args("pop", &args, 0) {
+            Ok(()) => arr.pop().unwrap_or(Object::None),
+            Err(e) => e,
+        },
+        "reverse" => |arr, args| match expect_args("reverse", &args, 0) {
+            Ok(()) => Object::Array(arr.iter().rev().cloned().collect()),
+            Err(e) => e,
+        },
+        "contains" => |arr, args| match expect_args("contains", &args, 1) {
+            Ok(()) => Object::Boolean(arr.iter().any(|el| is_same(el, &args[0]))),
+            Err(e) => e,
+        },
+        "join" => |arr, args| match expect_string("join", &args) {
+            Ok(sep) => Object::String(
+                arr.iter()
+                    .map(|el| match el {
+                        Object::String(s) => s.clone(),
+                        el => el.to_string(),
+                    })
+                    .collect::<Vec<_>>()
+                    .join(&sep),
+            ),
+            Err(e) => e,
+        },
+        "map" => |arr, args| {
+            if let Err(e) = expect_args("map", &args, 1) {
+                return e;
+            }
+
+            let mut result = vec![];
+            for el in arr.iter() {
+                match call_function(args[0].clone(), vec![el.clone()]) {
+                    err if err.is_error() => return err,
+                    obj => result.push(obj),
+                }
+            }
+
+            Object::Array(result)
+        },
+        "filter" => |arr, args| {
+            if let Err(e) = expect_args("filter", &args, 1) {
+                return e;
+            }
+
+            let mut result = vec![];
+            for el in arr.iter() {
+                match call_function(args[0].clone(), vec![el.clone()]) {
+                    Object::Boolean(true) => result.push(el.clone()),
+                    Object::Boolean(false) => {}
+                    err if err.is_error() => return err,
+                    obj => {
+                        return Object::error(
+                            ErrorKind::TypeError,
+                            format!(
+                                "'filter' expects the callback to return a boolean, got {}",
+                                obj
+                            ),
+                        )
+                    }
+                }
+            }
+
+            Object::Array(result)
+        },
+        "reduce" => |arr, args| {
+            if let Err(e) = expect_args("reduce", &args, 2) {
+                return e;
+            }
+
+            let mut acc = args[1].clone();
+            for el in arr.iter() {
+                acc = call_function(args[0].clone(), vec![acc, el.clone()]);
+
+                if acc.is_error() {
+                    return acc;
+                }
+            }
+
+            acc
+        },
+        _ => return None,
+    };
+
+    Some(method)
+}
+
+fn hashmap_method(name: &str) -> Option<HashMapMethod> {
+    let method: HashMapMethod = match name {
+        "len" => |pairs, args| match expect_args("len", &args, 0) {
+            Ok(()) => Object::Number(pairs.len() as i64),
+            Err(e) => e,
+        },
+        "keys" => |pairs, args| match expect_args("keys", &args, 0) {
+            Ok(()) => Object::Array(
+                sorted_keys(pairs)
+                    .into_iter()
+                    .map(|k| k.to_object())
+                    .collect(),
+            ),
+            Err(e) => e,
+        },
+        "values" => |pairs, args| match expect_args("values", &args, 0) {
+            Ok(()) => Object::Array(
+                sorted_keys(pairs)
+                    .into_iter()
+                    .map(|k| pairs[&k].clone())
+                    .collect(),
+            ),
+            Err(e) => e,
+        },
+        "contains_key" => |pairs, args| match expect_args("contains_key", &args, 1) {
+            Ok(()) => match args[0].hash() {
+                Some(hk) => Object::Boolean(pairs.contains_key(&hk)),
+                None => Object::error(
+                    ErrorKind::TypeError,
+                    format!("Key is not hashable, got {}", args[0]),
+                ),
+            },
+            Err(e) => e,
+        },
+        "remove" => |pairs, args| match expect_args("remove", &args, 1) {
+            Ok(()) => match args[0].hash() {
+                Some(hk) => pairs.remove(&hk).unwrap_or(Object::None),
+                None => Object::error(
+                    ErrorKind::TypeError,
+                    format!("Key is not hashable, got {}", args[0]),
+                ),
+            },
+            Err(e) => e,
+        },
+        _ => return None,
+    };
+
+    Some(method)
+}
+
+// Hashmaps are unordered, sorting keeps `keys` and `values` stable between runs
+#[allow(clippy::mutable_key_type)]
+fn sorted_keys(pairs: &HashMap<HashKey, Object>) -> Vec<HashKey> {
+    let mut keys = pairs.keys().cloned().collect::<Vec<_>>();
+    keys.sort();
+    keys
+}
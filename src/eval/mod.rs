@@ -1,76 +1,152 @@
-use std::collections::HashMap;
+use std::{cell::RefCell, collections::HashMap, rc::Rc};
 
-use crate::ast::statement::Block;
+use crate::ast::{
+    expression::Expression,
+    statement::{Block, Identifier, Statement},
+};
 
-use self::{env::Environment, object::Object};
+use self::{
+    env::Environment,
+    error::ErrorKind,
+    object::{CustomHash, Methods, Object},
+};
 
 pub mod env;
+pub mod error;
+pub mod methods;
+pub mod module;
 pub mod object;
 pub mod program;
 
 fn builtin_len(args: Vec<Object>) -> Object {
     if args.len() != 1 {
-        return Object::Error(format!(
-            "'len' does not accept more than 1 argument, got: {:?}",
-            args
-        ));
+        return Object::error(
+            ErrorKind::ArityError,
+            format!("'len' expects 1 argument, got {}", args.len()),
+        );
     }
 
-    match args.get(0).unwrap() {
+    match args.first().unwrap().thawed() {
         Object::String(s) => Object::Number(s.len().try_into().unwrap()),
         Object::Array(arr) => Object::Number(arr.len() as i64),
-        _ => Object::Error(format!("invalid argument, got: {:?}", args)),
+        Object::Range(r) => Object::Number(r.len()),
+        _ => Object::error(
+            ErrorKind::TypeError,
+            format!(
+                "'len' expects a string, array or range, got {}",
+                args[0].type_name()
+            ),
+        ),
     }
 }
 
+fn builtin_freeze(args: Vec<Object>) -> Object {
+    if args.len() != 1 {
+        return Object::error(
+            ErrorKind::ArityError,
+            format!("'freeze' expects 1 argument, got {}", args.len()),
+        );
+    }
+
+    args.into_iter().next().unwrap().freeze()
+}
+
 fn builtin_first(args: Vec<Object>) -> Object {
     if args.len() != 1 {
-        return Object::Error(format!(
-            "'first' does not accept more than 1 argument, got: {:?}",
-            args
-        ));
+        return Object::error(
+            ErrorKind::ArityError,
+            format!("'first' expects 1 argument, got {}", args.len()),
+        );
     }
 
-    match args.get(0).unwrap() {
+    match args.first().unwrap().thawed() {
         Object::Array(arr) => match arr.first() {
             None => Object::None,
             Some(v) => v.clone(),
         },
-        _ => Object::Error(format!("invalid argument, got: {:?}", args)),
+        _ => Object::error(
+            ErrorKind::TypeError,
+            format!("'first' expects an array, got {}", args[0].type_name()),
+        ),
     }
 }
 
 fn builtin_last(args: Vec<Object>) -> Object {
     if args.len() != 1 {
-        return Object::Error(format!(
-            "'last' does not accept more than 1 argument, got: {:?}",
-            args
-        ));
+        return Object::error(
+            ErrorKind::ArityError,
+            format!("'last' expects 1 argument, got {}", args.len()),
+        );
     }
 
-    match args.get(0).unwrap() {
+    match args.first().unwrap().thawed() {
         Object::Array(arr) => match arr.last() {
             None => Object::None,
             Some(v) => v.clone(),
         },
-        _ => Object::Error(format!("invalid argument, got: {:?}", args)),
+        _ => Object::error(
+            ErrorKind::TypeError,
+            format!("'last' expects an array, got {}", args[0].type_name()),
+        ),
+    }
+}
+
+/// Binds the named function declarations of a block before it runs, so they can
+/// be called from statements above them and from each other.
+pub fn hoist_functions(block: &[Statement], env: &mut Environment) {
+    for sttm in block {
+        match sttm {
+            Statement::Expression(
+                func @ Expression::Function {
+                    identifier: Some(_),
+                    ..
+                },
+            ) => {
+                func.eval(env);
+            }
+            Statement::Export(declaration) => {
+                hoist_functions(std::slice::from_ref(declaration.as_ref()), env)
+            }
+            _ => {}
+        }
     }
 }
 
 pub fn eval_block(block: &Block, env: &mut Environment) -> Object {
     let mut result = Object::None;
 
+    hoist_functions(block, env);
+
     for sttm in block {
-        match sttm.eval(env) {
-            Object::Return(r) => return Object::Return(r),
-            _ => result = sttm.eval(env),
+        result = sttm.eval(env);
+
+        if let Object::Return(_) = result {
+            return result;
+        }
+
+        if result.is_error() {
+            return result;
         }
     }
 
     result
 }
 
+/// Runs the block in its own scope, its `let`s are dropped at the end while
+/// assignments still reach the enclosing bindings.
+pub fn eval_scoped_block(block: &Block, env: &mut Environment) -> Object {
+    let mut scope = Environment::enclosed(env);
+
+    eval_block(block, &mut scope)
+}
+
+/// Runs a function body, its value is the first `return` or, like `if` blocks,
+/// the value of a trailing expression statement.
 pub fn eval_function_block(block: &Block, env: &mut Environment) -> Option<Object> {
+    let mut result = None;
+
+    hoist_functions(block, env);
+
     for sttm in block {
         match sttm.eval(env) {
             Object::Return(r) => {
@@ -82,11 +158,214 @@ pub fn eval_function_block(block: &Block, env: &mut Environment) -> Option<Objec
 
                 return Some(result);
             }
-            _ => sttm.eval(env),
-        };
+            err if err.is_error() => return Some(err),
+            obj => {
+                result = match sttm {
+                    Statement::Expression(_) => Some(obj),
+                    _ => None,
+                }
+            }
+        }
+    }
+
+    result
+}
+
+/// The items of an object for `for` loops and spreads, ranges are produced lazily.
+pub fn iterate(obj: Object) -> Result<Box<dyn Iterator<Item = Object>>, Object> {
+    match obj {
+        Object::Range(r) => Ok(Box::new(r.iter().map(Object::Number))),
+        Object::Array(arr) => Ok(Box::new(arr.into_iter())),
+        Object::Frozen(inner) => iterate(*inner),
+        Object::String(s) => {
+            let chars: Vec<Object> = s.chars().map(|c| Object::String(c.to_string())).collect();
+            Ok(Box::new(chars.into_iter()))
+        }
+        err if err.is_abrupt() => Err(err),
+        obj => Err(Object::error(
+            ErrorKind::TypeError,
+            format!("{} is not iterable", obj),
+        )),
+    }
+}
+
+/// Walks an assignment target such as `a.b[2].c` down to its root identifier,
+/// evaluating every key on the way so it only runs once.
+pub fn resolve_member_path(
+    target: &Expression,
+    env: &mut Environment,
+) -> Result<(Identifier, Vec<Object>), Object> {
+    match target {
+        Expression::Identifier(ident, _) => Ok((ident.clone(), vec![])),
+        Expression::Index { left, index } => {
+            let (root, mut keys) = resolve_member_path(left, env)?;
+
+            match index.eval(env) {
+                Object::Array(index) if index.len() == 1 => {
+                    keys.push(index.first().unwrap().clone());
+                    Ok((root, keys))
+                }
+                err if err.is_abrupt() => Err(err),
+                index => Err(Object::error(
+                    ErrorKind::IndexError,
+                    format!("invalid index, got {}", index),
+                )),
+            }
+        }
+        Expression::Dot {
+            identifier,
+            attribute,
+        } => {
+            let (root, mut keys) = resolve_member_path(identifier, env)?;
+            keys.push(Object::String(attribute.clone()));
+
+            Ok((root, keys))
+        }
+        _ => Err(Object::error(
+            ErrorKind::TypeError,
+            format!("Invalid assignment target, got {}", target),
+        )),
+    }
+}
+
+pub fn get_member(obj: &Object, key: &Object) -> Object {
+    match (obj.thawed(), key) {
+        (Object::Array(arr), Object::Number(n)) => match arr.get(*n as usize) {
+            Some(v) => v.clone(),
+            None => Object::None,
+        },
+        (Object::HashMap { pairs }, _) => match key.hash() {
+            Some(hk) => match pairs.get(&hk) {
+                Some(v) => v.clone(),
+                None => Object::None,
+            },
+            None => Object::error(
+                ErrorKind::TypeError,
+                format!("{} is not a valid key", key.type_name()),
+            ),
+        },
+        (Object::Struct { name, fields, .. }, Object::String(field)) => {
+            match fields.iter().find(|(f, _)| f == field) {
+                Some((_, v)) => v.clone(),
+                None => Object::error(
+                    ErrorKind::AttributeError,
+                    format!("{} has no field {}", name, field),
+                ),
+            }
+        }
+        _ => Object::error(
+            ErrorKind::TypeError,
+            format!("Cannot read {} of {}", key, obj.type_name()),
+        ),
+    }
+}
+
+/// Stores `val` at a place like `a.b[2]`. Targets that are not places, such as
+/// the result of a call, are left alone.
+pub fn write_back(target: &Expression, val: Object, env: &mut Environment) -> Result<(), Object> {
+    if !is_place(target) {
+        return Ok(());
+    }
+
+    let (root, keys) = resolve_member_path(target, env)?;
+    let current = match env.get(root.clone()) {
+        Some(obj) => obj,
+        None => return Err(env.not_found(&root)),
+    };
+
+    let updated = set_member(current, &keys, val)?;
+    env.assign(root, updated)
+}
+
+fn is_place(target: &Expression) -> bool {
+    place_root(target).is_some()
+}
+
+/// The variable a place like `a.b[2]` is stored in, `None` when it is not a place.
+pub fn place_root(target: &Expression) -> Option<&Identifier> {
+    match target {
+        Expression::Identifier(ident, _) => Some(ident),
+        Expression::Index { left, .. } => place_root(left),
+        Expression::Dot { identifier, .. } => place_root(identifier),
+        _ => None,
     }
+}
+
+/// Returns a copy of `obj` with the value at `keys` replaced by `val`.
+pub fn set_member(obj: Object, keys: &[Object], val: Object) -> Result<Object, Object> {
+    let (key, rest) = match keys.split_first() {
+        Some(k) => k,
+        None => return Ok(val),
+    };
+
+    match (obj, key) {
+        (Object::Array(mut arr), Object::Number(n)) => {
+            if *n < 0 || *n as usize >= arr.len() {
+                return Err(Object::error(
+                    ErrorKind::IndexError,
+                    format!("index out of bounds, got {} for length {}", n, arr.len()),
+                ));
+            }
+
+            let idx = *n as usize;
+            let current = std::mem::replace(&mut arr[idx], Object::None);
+            arr[idx] = set_member(current, rest, val)?;
+
+            Ok(Object::Array(arr))
+        }
+        (Object::HashMap { mut pairs }, _) => {
+            let hk = match key.hash() {
+                Some(hk) => hk,
+                None => {
+                    return Err(Object::error(
+                        ErrorKind::TypeError,
+                        format!("Key is not hashable, got {}", key),
+                    ))
+                }
+            };
+
+            let current = pairs.remove(&hk).unwrap_or(Object::None);
+            let updated = set_member(current, rest, val)?;
+            pairs.insert(hk, updated);
+
+            Ok(Object::HashMap { pairs })
+        }
+        (
+            Object::Struct {
+                name,
+                mut fields,
+                methods,
+            },
+            Object::String(field),
+        ) => {
+            let idx = match fields.iter().position(|(f, _)| f == field) {
+                Some(idx) => idx,
+                None => {
+                    return Err(Object::error(
+                        ErrorKind::AttributeError,
+                        format!("{} has no field {}", name, field),
+                    ))
+                }
+            };
 
-    None
+            let current = std::mem::replace(&mut fields[idx].1, Object::None);
+            fields[idx].1 = set_member(current, rest, val)?;
+
+            Ok(Object::Struct {
+                name,
+                fields,
+                methods,
+            })
+        }
+        (Object::Frozen(inner), _) => Err(Object::error(
+            ErrorKind::AssignmentError,
+            format!("Cannot assign {} of {}, it is frozen", key, inner),
+        )),
+        (obj, _) => Err(Object::error(
+            ErrorKind::TypeError,
+            format!("Cannot assign {} of {}", key, obj),
+        )),
+    }
 }
 
 pub fn builtin_functions() -> Environment {
@@ -95,14 +374,54 @@ pub fn builtin_functions() -> Environment {
         func: builtin_first,
     };
     let last_func = Object::Builtin { func: builtin_last };
+    let freeze_func = Object::Builtin {
+        func: builtin_freeze,
+    };
 
     let mut store: HashMap<String, Object> = HashMap::new();
 
     store.insert(String::from("len"), len_func);
     store.insert(String::from("first"), first_func);
     store.insert(String::from("last"), last_func);
+    store.insert(String::from("freeze"), freeze_func);
 
-    Environment { store }
+    // `Ok(v)` and `Err(e)` are the variants of a builtin enum, so they can be
+    // matched as `Result.Ok(v)` and extended with `impl Result`
+    let result_methods = Methods::default();
+    let result_variants = [("Ok", "value"), ("Err", "error")];
+
+    store.insert(
+        String::from("Result"),
+        Object::EnumType {
+            name: String::from("Result"),
+            variants: result_variants
+                .iter()
+                .map(|(variant, field)| (variant.to_string(), vec![field.to_string()]))
+                .collect(),
+            methods: Rc::clone(&result_methods),
+        },
+    );
+
+    for (variant, field) in result_variants {
+        store.insert(
+            variant.to_string(),
+            Object::EnumVariant {
+                name: String::from("Result"),
+                variant: variant.to_string(),
+                fields: vec![field.to_string()],
+                methods: Rc::clone(&result_methods),
+            },
+        );
+    }
+
+    Environment {
+        store: Rc::new(RefCell::new(store)),
+        bindings: Rc::default(),
+        file: None,
+        modules: Rc::default(),
+        calls: Rc::default(),
+        outer: None,
+    }
 }
 
 #[cfg(test)]
@@ -243,7 +562,7 @@ mod test {
     fn eval_reassign_statements() {
         let mut env = Environment::new();
         let inputs = ["let a = 5; a = 10; a;", "b = 10;"];
-        let expected = ["10", "error: Identifier not found: b"];
+        let expected = ["10", "error: identifier not found: b"];
 
         for (i, input) in inputs.iter().enumerate() {
             let program = Parser::build_ast(input);
@@ -327,7 +646,12 @@ mod test {
             "len(\"hello world\")",
             "len(1)",
         ];
-        let expected = ["0", "4", "11", "error: invalid argument, got: [Number(1)]"];
+        let expected = [
+            "0",
+            "4",
+            "11",
+            "error: 'len' expects a string, array or range, got number",
+        ];
 
         for (i, input) in inputs.iter().enumerate() {
             let program = Parser::build_ast(input);
@@ -472,4 +796,939 @@ mod test {
             assert_eq!(result.to_string(), expected.get(i).unwrap().to_string());
         }
     }
+
+    #[test]
+    fn eval_member_assignment() {
+        let mut env = Environment::new();
+        let inputs = [
+            "let arr = [1, 2, 3]; arr[0] = 10; arr;",
+            "let map = {\"k\": 1}; map[\"k\"] = 2; map.k;",
+            "let map = {\"k\": 1}; map.other = 3; map[\"other\"];",
+            "let a = {\"b\": [0, 0, {\"c\": 1}]}; a.b[2].c = 5; a.b[2].c;",
+            "let arr = [1]; arr[3] = 1;",
+            "let n = 1; n.key = 1;",
+        ];
+        let expected = [
+            "[ 10, 2, 3 ]",
+            "2",
+            "3",
+            "5",
+            "error: index out of bounds, got 3 for length 1",
+            "error: Cannot assign \"key\" of 1",
+        ];
+
+        for (i, input) in inputs.iter().enumerate() {
+            let program = Parser::build_ast(input);
+            let result = program.eval_statements(&mut env);
+            assert_eq!(result.to_string(), expected.get(i).unwrap().to_string());
+        }
+    }
+
+    #[test]
+    fn eval_compound_assignment() {
+        let mut env = Environment::new();
+        let inputs = [
+            "let a = 5; a += 2; a;",
+            "let a = 5; a -= 2; a;",
+            "let a = 5; a *= 2; a;",
+            "let a = 10; a /= 2; a;",
+            "let arr = [1, 2]; arr[1] *= 3; arr;",
+            "let m = {\"count\": 1}; m.count += 1; m.count;",
+            "let i = 0; while (i < 3) { i += 1; } i;",
+        ];
+        let expected = ["7", "3", "10", "5", "[ 1, 6 ]", "2", "3"];
+
+        for (i, input) in inputs.iter().enumerate() {
+            let program = Parser::build_ast(input);
+            let result = program.eval_statements(&mut env);
+            assert_eq!(result.to_string(), expected.get(i).unwrap().to_string());
+        }
+    }
+
+    #[test]
+    fn eval_destructuring() {
+        let mut env = Environment::new();
+        let inputs = [
+            "let [a, b, ...rest] = [1, 2, 3, 4]; a + b;",
+            "let [a, b, ...rest] = [1, 2, 3, 4]; rest;",
+            "let [a, b, c] = [1]; c;",
+            "let {name, age: years} = {\"name\": \"dan\", \"age\": 30}; years;",
+            "let {name, missing} = {\"name\": \"dan\"}; missing;",
+            "let [x, {y}] = [1, {\"y\": 2}]; x + y;",
+            "let [a] = 1;",
+            "fn add([a, b], {c}) { return a + b + c; }; add([1, 2], {\"c\": 3});",
+        ];
+        let expected = [
+            "3",
+            "[ 3, 4 ]",
+            "None",
+            "30",
+            "None",
+            "3",
+            "error: Cannot destructure 1 as an array",
+            "6",
+        ];
+
+        for (i, input) in inputs.iter().enumerate() {
+            let program = Parser::build_ast(input);
+            let result = program.eval_statements(&mut env);
+            assert_eq!(result.to_string(), expected.get(i).unwrap().to_string());
+        }
+    }
+
+    #[test]
+    fn eval_match_expression() {
+        let mut env = Environment::new();
+        let inputs = [
+            "match (\"stop\") { \"start\" => 1, \"stop\" | \"halt\" => 2, _ => 3 }",
+            "match (5) { 1 => \"one\", n if n > 3 => n * 2, _ => 0 }",
+            "match (2) { n if n > 3 => n * 2, _ => 0 }",
+            "match ([1, 2, 3]) { [a] => a, [a, ...rest] => rest }",
+            "match ({\"kind\": \"circle\", \"r\": 2}) { {kind: \"square\", side} => side, {kind: \"circle\", r} => r * r }",
+            "match (-1) { -1 => true, _ => false }",
+            "match (7) { 1 => 1, 2 => 2 }",
+            "match (1) { x => { let y = x + 1; y; } }",
+            "let n = 100; match (5) { n if n > 10 => 1, _ => 2 }; n;",
+            "match (3) { m => m }; m;",
+            "let total = 0; match (4) { k => { total = total + k; } }; total;",
+        ];
+        let expected = [
+            "2",
+            "10",
+            "0",
+            "[ 2, 3 ]",
+            "4",
+            "true",
+            "error: non-exhaustive match, no arm matched 7",
+            "2",
+            "100",
+            "error: identifier not found: m",
+            "4",
+        ];
+
+        for (i, input) in inputs.iter().enumerate() {
+            let program = Parser::build_ast(input);
+            let result = program.eval_statements(&mut env);
+            assert_eq!(result.to_string(), expected.get(i).unwrap().to_string());
+        }
+    }
+
+    #[test]
+    fn eval_optional_chaining() {
+        let mut env = Environment::new();
+        let inputs = [
+            "let a = {\"b\": {\"c\": 1}}; a.b?.c;",
+            "let a = {\"x\": 1}; a.b?.c;",
+            "let a = {\"x\": 1}; a.b?.c.d;",
+            "let a = {\"x\": 1}; a.b?[0];",
+            "let a = {\"b\": [5]}; a.b?[0];",
+            "let a = {\"x\": 1}; a.b?.c();",
+            "let a = {\"x\": 1}; a.b.c;",
+        ];
+        let expected = [
+            "1",
+            "None",
+            "None",
+            "None",
+            "5",
+            "None",
+            "error: Cannot read c of none",
+        ];
+
+        for (i, input) in inputs.iter().enumerate() {
+            let program = Parser::build_ast(input);
+            let result = program.eval_statements(&mut env);
+            assert_eq!(result.to_string(), expected.get(i).unwrap().to_string());
+        }
+    }
+
+    #[test]
+    fn eval_null_coalescing() {
+        let mut env = Environment::new();
+        let inputs = [
+            "let a = {\"x\": 1}; a.y ?? 10;",
+            "let a = {\"x\": 1}; a.x ?? 10;",
+            "let a = {\"x\": 1}; a.y ?? a.z ?? 3;",
+            "let a = {\"x\": false}; a.x ?? true;",
+            "let a = {}; a?.b?.c ?? \"default\";",
+            "1 ?? missing;",
+        ];
+        let expected = ["10", "1", "3", "false", "\"default\"", "1"];
+
+        for (i, input) in inputs.iter().enumerate() {
+            let program = Parser::build_ast(input);
+            let result = program.eval_statements(&mut env);
+            assert_eq!(result.to_string(), expected.get(i).unwrap().to_string());
+        }
+    }
+
+    #[test]
+    fn eval_ranges() {
+        let mut env = Environment::new();
+        let inputs = [
+            "0..5;",
+            "0..=10 step 5;",
+            "len(0..10 step 3);",
+            "(0..10 step 3)[2];",
+            "(0..3)[5];",
+            "5 in 0..10;",
+            "10 in 0..10;",
+            "3 in 0..10 step 2;",
+            "[10, 20, 30, 40][1..3];",
+            "[10, 20, 30, 40][0..=3 step 2];",
+            "0..10 step 0;",
+            "\"a\"..3;",
+            "0..nope;",
+            "try { 0..(1 / 0) step 0; } catch (e) { e.kind }",
+            "len(-9223372036854775807..=9223372036854775807);",
+            "0 in -9223372036854775807..=9223372036854775807 step 3;",
+            "[1, 2, 3][0..100000000000];",
+            "[1, 2, 3][-100000000000..2];",
+            "[1, 2, 3][10..=-10 step -1];",
+        ];
+        let expected = [
+            "0..5",
+            "0..=10 step 5",
+            "4",
+            "6",
+            "None",
+            "true",
+            "false",
+            "false",
+            "[ 20, 30 ]",
+            "[ 10, 30 ]",
+            "error: range step cannot be zero",
+            "error: Range bounds and step must be numbers, got: \"a\", 3 and 1",
+            "error: identifier not found: nope",
+            "\"DivisionByZero\"",
+            "9223372036854775807",
+            "false",
+            "[ 1, 2, 3 ]",
+            "[ 1, 2 ]",
+            "[ 3, 2, 1 ]",
+        ];
+
+        for (i, input) in inputs.iter().enumerate() {
+            let program = Parser::build_ast(input);
+            let result = program.eval_statements(&mut env);
+            assert_eq!(result.to_string(), expected.get(i).unwrap().to_string());
+        }
+    }
+
+    #[test]
+    fn eval_for_loops() {
+        let mut env = Environment::new();
+        let inputs = [
+            "let sum = 0; for (i in 0..5) { sum += i; } sum;",
+            "let sum = 0; for (i in 10..=0 step -5) { sum += i; } sum;",
+            "let total = 0; for (x in [1, 2, 3]) { total += x; } total;",
+            "let total = 0; for ([a, b] in [[1, 2], [3, 4]]) { total += a * b; } total;",
+            "fn find(xs) { for (x in xs) { if (x > 2) { return x; } } return 0; }; find([1, 5, 3]);",
+            "2 in [1, 2, 3];",
+            "\"b\" in {\"b\": 1};",
+            "for (x in 5) { }",
+            "let i = 100; for (i in 0..3) { } i;",
+            "for (j in 0..3) { }; j;",
+            "fn first_big() { for (i in 0..1000000000000) { if (i == 3) { return i; } } }; first_big();",
+        ];
+        let expected = [
+            "10",
+            "15",
+            "6",
+            "14",
+            "5",
+            "true",
+            "true",
+            "error: 5 is not iterable",
+            "100",
+            "error: identifier not found: j",
+            "3",
+        ];
+
+        for (i, input) in inputs.iter().enumerate() {
+            let program = Parser::build_ast(input);
+            let result = program.eval_statements(&mut env);
+            assert_eq!(result.to_string(), expected.get(i).unwrap().to_string());
+        }
+    }
+
+    #[test]
+    fn eval_function_parameters() {
+        let mut env = Environment::new();
+        let inputs = [
+            "fn f(x, y = 10) { return x + y; }; f(1);",
+            "fn f(x, y = x * 2) { return x + y; }; f(2);",
+            "fn f(x, y = 10) { return x - y; }; f(y: 1, x: 5);",
+            "fn f(first, ...others) { return others; }; f(1, 2, 3);",
+            "fn f(first, ...others) { return others; }; f(1);",
+            "fn add(x, y) { return x + y; }; add(1);",
+            "fn add(x, y) { return x + y; }; add(1, 2, 3);",
+            "let add = fn(x, y = 1) { return x + y; }; add(1, z: 2);",
+            "fn add(x, y) { return x + y; }; add(1, x: 2);",
+            "len(x: 1);",
+            "fn f(x, y) { x }; f(z: 1);",
+            "fn f(x, y = 1 / 0) { x }; f(1);",
+            "fn f(x = missing) { x }; try { f(); } catch (e) { e.kind }",
+        ];
+        let expected = [
+            "11",
+            "6",
+            "4",
+            "[ 2, 3 ]",
+            "[  ]",
+            "error: Missing argument for parameter y of fn add(x, y) at 1:36",
+            "error: Too many arguments for fn add(x, y): expected at most 2, got 3 at 1:36",
+            "error: Unknown argument z for fn add(x, y?) at 1:46",
+            "error: Argument x for fn add(x, y) was given more than once at 1:36",
+            "error: Builtin functions do not accept named arguments at 1:4",
+            "error: Unknown argument z for fn f(x, y) at 1:20",
+            "error: division by zero",
+            "\"NameError\"",
+        ];
+
+        for (i, input) in inputs.iter().enumerate() {
+            let program = Parser::build_ast(input);
+            let result = program.eval_statements(&mut env);
+            assert_eq!(result.to_string(), expected.get(i).unwrap().to_string());
+        }
+    }
+
+    #[test]
+    fn eval_function_scopes() {
+        let mut env = Environment::new();
+        let inputs = [
+            "let x = 1; fn f(x) { return x; }; f(2); x;",
+            "fn f() { let inner = 1; return inner; }; f(); inner;",
+            "let counter = 0; fn inc() { counter = counter + 1; return counter; }; inc(); inc(); counter;",
+            "fn fact(n) { if (n == 0) { return 1; } return fact(n - 1) * n; }; fact(5);",
+        ];
+        let expected = ["1", "error: identifier not found: inner", "2", "120"];
+
+        for (i, input) in inputs.iter().enumerate() {
+            let program = Parser::build_ast(input);
+            let result = program.eval_statements(&mut env);
+            assert_eq!(result.to_string(), expected.get(i).unwrap().to_string());
+        }
+    }
+
+    #[test]
+    fn eval_spread() {
+        let mut env = Environment::new();
+        let inputs = [
+            "let a = [1, 2]; let b = [3]; [...a, ...b, 4];",
+            "[0, ...1..=3];",
+            "let defaults = {\"x\": 1, \"y\": 2}; let overrides = {\"y\": 3}; let m = {...defaults, ...overrides}; m.x + m.y;",
+            "let defaults = {\"x\": 1}; let m = {\"x\": 5, ...defaults}; m.x;",
+            "fn add(x, y, z) { return x + y + z; }; let args = [1, 2]; add(...args, 3);",
+            "[...5];",
+            "{...[1]};",
+        ];
+        let expected = [
+            "[ 1, 2, 3, 4 ]",
+            "[ 0, 1, 2, 3 ]",
+            "4",
+            "5",
+            "6",
+            "error: 5 is not iterable",
+            "error: Cannot spread [ 1 ] into a hashmap, it is not a hashmap",
+        ];
+
+        for (i, input) in inputs.iter().enumerate() {
+            let program = Parser::build_ast(input);
+            let result = program.eval_statements(&mut env);
+            assert_eq!(result.to_string(), expected.get(i).unwrap().to_string());
+        }
+    }
+
+    #[test]
+    fn eval_arrow_functions() {
+        let mut env = Environment::new();
+        let inputs = [
+            "let add = (x, y) => x + y; add(1, 2);",
+            "let double = x => x * 2; double(21);",
+            "let apply = (f, x) => f(x); apply(x => x + 1, 1);",
+            "let adder = x => y => x + y; adder(2)(3);",
+            "let f = x => { let y = x * 3; return y; }; f(2);",
+            "let g = () => [1, 2]; len(g());",
+        ];
+        let expected = ["3", "42", "2", "5", "6", "2"];
+
+        for (i, input) in inputs.iter().enumerate() {
+            let program = Parser::build_ast(input);
+            let result = program.eval_statements(&mut env);
+            assert_eq!(result.to_string(), expected.get(i).unwrap().to_string());
+        }
+    }
+
+    #[test]
+    fn eval_implicit_return() {
+        let mut env = Environment::new();
+        let inputs = [
+            "fn add(x, y) { x + y }; add(1, 2);",
+            "fn sign(n) { if (n < 0) { -1 } else { if (n == 0) { 0 } else { 1 } } }; sign(-5);",
+            "sign(0);",
+            "sign(7);",
+            "fn early(n) { if (n > 1) { return 10; }; n }; early(5) + early(1);",
+            "fn nothing() { let x = 1; }; nothing();",
+            "let f = fn(x) { x * 2; }; f(4);",
+        ];
+        let expected = ["3", "-1", "0", "1", "11", "None", "8"];
+
+        for (i, input) in inputs.iter().enumerate() {
+            let program = Parser::build_ast(input);
+            let result = program.eval_statements(&mut env);
+            assert_eq!(result.to_string(), expected.get(i).unwrap().to_string());
+        }
+    }
+
+    #[test]
+    fn eval_function_hoisting() {
+        let mut env = Environment::new();
+        let inputs = [
+            "let a = double(4); fn double(x) { return x * 2; }; a;",
+            "fn is_even(n) { if (n == 0) { return true; } return is_odd(n - 1); }; fn is_odd(n) { if (n == 0) { return false; } return is_even(n - 1); }; is_even(10);",
+            "fn outer() { return inner(); fn inner() { return 3; } }; outer();",
+            "if (true) { let h = helper(); fn helper() { 5 }; h };",
+            "fn later() { return 1; }; let later = 2; later;",
+        ];
+        let expected = ["8", "true", "3", "5", "2"];
+
+        for (i, input) in inputs.iter().enumerate() {
+            let program = Parser::build_ast(input);
+            let result = program.eval_statements(&mut env);
+            assert_eq!(result.to_string(), expected.get(i).unwrap().to_string());
+        }
+    }
+
+    #[test]
+    fn eval_constants() {
+        let mut env = Environment::new();
+        let inputs = [
+            "const LIMIT = 10; LIMIT * 2;",
+            "LIMIT = 5;",
+            "LIMIT += 1;",
+            "let LIMIT = 1;",
+            "const LIMIT = 2;",
+            "const XS = [1, 2]; XS[0] = 5;",
+            "fn shadow() { let LIMIT = 3; LIMIT = 4; LIMIT }; shadow();",
+            "LIMIT;",
+        ];
+        let expected = [
+            "20",
+            "error: Cannot assign to constant LIMIT",
+            "error: Cannot assign to constant LIMIT",
+            "error: Cannot redeclare constant LIMIT",
+            "error: Cannot redeclare constant LIMIT",
+            "error: Cannot assign to constant XS",
+            "4",
+            "10",
+        ];
+
+        for (i, input) in inputs.iter().enumerate() {
+            let program = Parser::build_ast(input);
+            let result = program.eval_statements(&mut env);
+            assert_eq!(result.to_string(), expected.get(i).unwrap().to_string());
+        }
+    }
+
+    #[test]
+    fn eval_builtin_freeze() {
+        let mut env = Environment::new();
+        let inputs = [
+            "let xs = freeze([1, [2, 3]]); xs[0] = 5;",
+            "xs[1][0] = 5;",
+            "let inner = xs[1]; inner[1] = 4;",
+            "let m = freeze({\"a\": {\"b\": 1}}); m.a.b = 2;",
+            "m.a.b + len(xs) + xs[1][1];",
+            "xs = [9]; xs[0] = 1; xs;",
+            "let copy = {...m}; copy.c = 1; copy.c;",
+            "let [a, b] = xs; a;",
+            "freeze(5);",
+        ];
+        let expected = [
+            "error: Cannot assign 0 of [ 1, [ 2, 3 ] ], it is frozen",
+            "error: Cannot assign 1 of [ 1, [ 2, 3 ] ], it is frozen",
+            "error: Cannot assign 1 of [ 2, 3 ], it is frozen",
+            "error: Cannot assign \"a\" of { \"a\" : { \"b\" : 1 } }, it is frozen",
+            "6",
+            "[ 1 ]",
+            "1",
+            "1",
+            "5",
+        ];
+
+        for (i, input) in inputs.iter().enumerate() {
+            let program = Parser::build_ast(input);
+            let result = program.eval_statements(&mut env);
+            assert_eq!(result.to_string(), expected.get(i).unwrap().to_string());
+        }
+    }
+
+    #[test]
+    fn eval_structs() {
+        let mut env = Environment::new();
+        let inputs = [
+            "struct Point { x, y }",
+            "let p = Point(1, 2); p;",
+            "let q = Point(y: 4, x: 3); q.x + q.y;",
+            "p.x = 10; p.y += 1; p;",
+            "p.z;",
+            "p.z = 1;",
+            "Point(1);",
+            "Point(1, 2, 3);",
+            "Point(1, 2, z: 3);",
+            "struct Line { from, to }; let l = Line(p, q); l.to.y = 7; l.to;",
+            "let frozen = freeze(Point(0, 0)); frozen.x = 1;",
+        ];
+        let expected = [
+            "struct Point { x, y }",
+            "Point { x: 1, y: 2 }",
+            "7",
+            "Point { x: 10, y: 3 }",
+            "error: Point has no field z",
+            "error: Point has no field z",
+            "error: Missing field y for Point at 1:6",
+            "error: Too many fields for Point: expected 2, got 3 at 1:6",
+            "error: Point has no field z at 1:6",
+            "Point { x: 3, y: 7 }",
+            "error: Cannot assign \"x\" of Point { x: 0, y: 0 }, it is frozen",
+        ];
+
+        for (i, input) in inputs.iter().enumerate() {
+            let program = Parser::build_ast(input);
+            let result = program.eval_statements(&mut env);
+            assert_eq!(result.to_string(), expected.get(i).unwrap().to_string());
+        }
+    }
+
+    #[test]
+    fn eval_enums() {
+        let mut env = Environment::new();
+        let inputs = [
+            "enum Shape { Circle(r), Rect(w, h), Empty }",
+            "let c = Shape.Circle(2); c;",
+            "Shape.Empty;",
+            "Shape.Rect(w: 2, h: 3).h;",
+            "fn area(s) { match (s) { Shape.Circle(r) => 3 * r * r, Shape.Rect(w, h) => w * h, Shape.Empty => 0 } }; area(c) + area(Shape.Rect(2, 3)) + area(Shape.Empty);",
+            "c == Shape.Circle(2);",
+            "c != Shape.Circle(3);",
+            "Shape.Empty == 1;",
+            "let names = {Shape.Empty: \"empty\", Shape.Circle(2): \"circle\"}; names[c];",
+            "Shape.Circle(2) in names;",
+            "Shape.Triangle;",
+            "Shape.Rect(1);",
+            "let keyed = {Shape.Empty: 1, \"Shape.Empty\": 2}; [keyed.keys().len(), keyed[Shape.Empty], keyed[\"Shape.Empty\"]];",
+            "Shape.Empty in keyed.keys();",
+        ];
+        let expected = [
+            "enum Shape { Circle(r), Rect(w, h), Empty }",
+            "Shape.Circle(2)",
+            "Shape.Empty",
+            "3",
+            "18",
+            "true",
+            "true",
+            "false",
+            "\"circle\"",
+            "true",
+            "error: Shape has no variant Triangle",
+            "error: Missing field h for Shape.Rect at 1:11",
+            "[ 2, 1, 2 ]",
+            "true",
+        ];
+
+        for (i, input) in inputs.iter().enumerate() {
+            let program = Parser::build_ast(input);
+            let result = program.eval_statements(&mut env);
+            assert_eq!(result.to_string(), expected.get(i).unwrap().to_string());
+        }
+    }
+
+    #[test]
+    fn eval_methods() {
+        let mut env = Environment::new();
+        let inputs = [
+            "struct Point { x, y }; impl Point { fn norm() { self.x + self.y } fn scale(k) { self.x *= k; self.y *= k; self } fn origin() { Point(0, 0) } fn me() { self } }",
+            "let p = Point(1, 2); p.norm();",
+            "p.scale(3); p;",
+            "Point.origin();",
+            "Point(2, 2).scale(2).norm();",
+            "let shapes = [Point(1, 1)]; shapes[0].scale(5); shapes[0].x;",
+            "const ONE = Point(1, 1); ONE.norm();",
+            "ONE.scale(2);",
+            "enum Shape { Circle(r), Empty }; impl Shape { fn area() { match (self) { Shape.Circle(r) => 3 * r * r, Shape.Empty => 0 } } fn unit() { Shape.Circle(1) } }; Shape.unit().area();",
+            "Point.missing();",
+            "Point.me();",
+            "struct S { f }; impl S { fn get() { 1 } }; const s = S(fn() { 1 }); s.get();",
+        ];
+        let expected = [
+            "None",
+            "3",
+            "Point { x: 3, y: 6 }",
+            "Point { x: 0, y: 0 }",
+            "8",
+            "5",
+            "2",
+            "error: Cannot assign to constant ONE",
+            "3",
+            "error: Point has no function missing",
+            "error: identifier not found: self",
+            "1",
+        ];
+
+        for (i, input) in inputs.iter().enumerate() {
+            let program = Parser::build_ast(input);
+            let result = program.eval_statements(&mut env);
+            assert_eq!(result.to_string(), expected.get(i).unwrap().to_string());
+        }
+    }
+
+    #[test]
+    fn eval_builtin_methods() {
+        let mut env = Environment::new();
+        let inputs = [
+            "\"abc\".len();",
+            "\" Dang \".trim().upper();",
+            "\"a,b,c\".split(\",\").join(\"-\");",
+            "let xs = [1, 2, 3]; xs.first() + xs.last();",
+            "xs.push(4); xs;",
+            "xs.pop() + xs.len();",
+            "xs.filter(x => x > 1).map(x => x * 10).len();",
+            "xs.map(x => x * 2).reduce((acc, x) => acc + x, 0);",
+            "let m = {\"b\": 2, \"a\": 1}; m.keys();",
+            "m.values();",
+            "m.remove(\"a\"); m;",
+            "let obj = {\"len\": fn() { 42 }}; obj.len();",
+            "let frozen = freeze([1]); frozen.push(2);",
+            "frozen.map(x => x + 1);",
+            "xs.push();",
+            "xs.filter(x => x);",
+            "xs.nope();",
+            "let grid = [[1], [2]]; grid[1].push(3); grid;",
+            "const m = {\"f\": fn() { 1 }}; [m.len(), m.keys()];",
+            "const ys = [1]; ys.push(2);",
+            "ys;",
+        ];
+        let expected = [
+            "3",
+            "\"DANG\"",
+            "\"a-b-c\"",
+            "4",
+            "[ 1, 2, 3, 4 ]",
+            "7",
+            "2",
+            "12",
+            "[ \"a\", \"b\" ]",
+            "[ 1, 2 ]",
+            "{ \"b\" : 2 }",
+            "42",
+            "error: Cannot call push on [ 1 ], it is frozen",
+            "[ 2 ]",
+            "error: 'push' expects 1 argument(s), got 0",
+            "error: 'filter' expects the callback to return a boolean, got 1",
+            "error: Cannot read nope of array",
+            "[ [ 1 ], [ 2, 3 ] ]",
+            "[ 1, [ \"f\" ] ]",
+            "error: Cannot assign to constant ys",
+            "[ 1 ]",
+        ];
+
+        for (i, input) in inputs.iter().enumerate() {
+            let program = Parser::build_ast(input);
+            let result = program.eval_statements(&mut env);
+            assert_eq!(result.to_string(), expected.get(i).unwrap().to_string());
+        }
+    }
+
+    #[test]
+    fn eval_block_scopes() {
+        let mut env = Environment::new();
+        let inputs = [
+            "let x = 1; let y = 0; if (true) { let x = 5; y = x; }; x + y;",
+            "if (false) { 1 } else { let inner = 2; }; inner;",
+            "let i = 0; while (i < 3) { let step = i; i = i + 1; }; i;",
+            "step;",
+            "{ let z = 3; y = y + z; }; y;",
+            "z;",
+            "{ let x = 10; { x = x + 1; y = x; } }; [x, y];",
+            "{ fn helper() { 1 } }; helper;",
+            "{};",
+            "if (true) {};",
+        ];
+        let expected = [
+            "6",
+            "error: identifier not found: inner",
+            "3",
+            "error: identifier not found: step",
+            "8",
+            "error: identifier not found: z",
+            "[ 1, 11 ]",
+            "error: identifier not found: helper",
+            "{  }",
+            "None",
+        ];
+
+        for (i, input) in inputs.iter().enumerate() {
+            let program = Parser::build_ast(input);
+            let result = program.eval_statements(&mut env);
+            assert_eq!(result.to_string(), expected.get(i).unwrap().to_string());
+        }
+    }
+
+    #[test]
+    fn eval_tail_calls() {
+        let mut env = Environment::new();
+        let inputs = [
+            "fn countdown(n) { if (n == 0) { return \"done\"; } return countdown(n - 1); }; countdown(100000);",
+            "fn is_even(n) { if (n == 0) { return true; } else { return is_odd(n - 1); } }; fn is_odd(n) { if (n == 0) { return false; } else { return is_even(n - 1); } }; is_even(100001);",
+            "let sum = fn(n, acc) { if (n == 0) { return acc; } return sum(n - 1, acc + n); }; sum(100000, 0);",
+            "let spin = (n) => { if (n == 0) { return 0; } return spin(n - 1); }; spin(100000);",
+            "fn wrap(x) { return len(x); }; wrap([1, 2]);",
+            "return countdown(3);",
+        ];
+        let expected = ["\"done\"", "false", "5000050000", "0", "2", "\"done\""];
+
+        for (i, input) in inputs.iter().enumerate() {
+            let program = Parser::build_ast(input);
+            let result = program.eval_statements(&mut env);
+            assert_eq!(result.to_string(), expected.get(i).unwrap().to_string());
+        }
+    }
+
+    #[test]
+    fn eval_recursion_limit() {
+        let mut env = Environment::new();
+        env.set_max_depth(20);
+
+        let inputs = [
+            "fn depth(n) { if (n == 0) { return 0; } 1 + depth(n - 1) }; depth(19);",
+            "depth(25);",
+            "depth(5);",
+            "fn countdown(n) { if (n == 0) { return 0; } return countdown(n - 1); }; countdown(1000);",
+            "let forever = fn(n) { forever(n + 1) }; forever(0);",
+            "[1].map(fn(x) { depth(x + 30) });",
+        ];
+        let expected = [
+            "19",
+            "error: maximum recursion depth exceeded in depth at 1:50",
+            "5",
+            "0",
+            "error: maximum recursion depth exceeded in forever at 1:30",
+            "error: maximum recursion depth exceeded in depth at 1:50",
+        ];
+
+        for (i, input) in inputs.iter().enumerate() {
+            let program = Parser::build_ast(input);
+            let result = program.eval_statements(&mut env);
+            assert_eq!(result.to_string(), expected.get(i).unwrap().to_string());
+        }
+    }
+
+    #[test]
+    fn eval_recursion_limit_on_a_normal_stack() {
+        // a stack overflow would abort the whole test run instead of failing it
+        let run = |input: &'static str| {
+            std::thread::Builder::new()
+                .stack_size(2 * 1024 * 1024)
+                .spawn(move || {
+                    let mut env = Environment::new();
+                    Parser::build_ast(input)
+                        .eval_statements(&mut env)
+                        .to_string()
+                })
+                .unwrap()
+                .join()
+                .unwrap()
+        };
+
+        assert_eq!(
+            run("fn depth(n) { if (n == 0) { return 0; } 1 + depth(n - 1) }; depth(1000);"),
+            "error: maximum recursion depth exceeded in depth at 1:50"
+        );
+        assert_eq!(
+            run("fn depth(n) { if (n == 0) { return 0; } 1 + depth(n - 1) }; depth(10);"),
+            "10"
+        );
+    }
+
+    #[test]
+    fn eval_try_catch() {
+        let mut env = Environment::new();
+        let inputs = [
+            "try { 1 / 0; } catch (e) { [e.kind, e.message] }",
+            "try { missing; } catch (e) { e.message }",
+            "try { [1][true]; } catch (e) { e.kind }",
+            "try { throw \"boom\"; } catch (e) { [e.kind, e.message, e.value] }",
+            "try { throw {\"code\": 4}; } catch (e) { e.value.code }",
+            "try { try { throw \"again\"; } catch (e) { throw e; } } catch (e) { e.message }",
+            "let log = []; try { try { throw \"inner\"; } finally { log.push(\"cleanup\"); } } catch (e) { log.push(e.message); }; log;",
+            "fn risky(n) { if (n > 2) { throw \"too big\"; } return n; }; fn safe(n) { try { return risky(n); } catch (e) { return -1; } }; [safe(1), safe(5)];",
+            "let i = 0; try { while (true) { i = i + 1; if (i == 3) { throw \"stop\"; } } } catch (e) { i }",
+            "try { [1, 2].map(fn(x) { throw \"in callback\"; }); } catch (e) { e.message }",
+            "try { 1 } catch (e) { 2 } finally { 3 }",
+            "let after = 0; throw \"uncaught\"; after = 1;",
+            "after;",
+        ];
+        let expected = [
+            "[ \"DivisionByZero\", \"division by zero\" ]",
+            "\"identifier not found: missing\"",
+            "\"TypeError\"",
+            "[ \"Error\", \"boom\", \"boom\" ]",
+            "4",
+            "\"again\"",
+            "[ \"cleanup\", \"inner\" ]",
+            "[ 1, -1 ]",
+            "3",
+            "\"in callback\"",
+            "1",
+            "error: uncaught",
+            "0",
+        ];
+
+        for (i, input) in inputs.iter().enumerate() {
+            let program = Parser::build_ast(input);
+            let result = program.eval_statements(&mut env);
+            assert_eq!(result.to_string(), expected.get(i).unwrap().to_string());
+        }
+    }
+
+    #[test]
+    fn eval_result_values() {
+        let mut env = Environment::new();
+        let inputs = [
+            "fn half(n) { if (n == 0) { return Err(\"zero\"); } return Ok(n / 2); }; half(4);",
+            "half(0);",
+            "fn quarter(n) { let h = half(n)?; Ok(half(h)? + 0) }; [quarter(8), quarter(0)];",
+            "fn sum(xs) { let total = 0; for (x in xs) { total = total + half(x)?; } Ok(total) }; [sum([2, 4]), sum([2, 0])];",
+            "fn count(xs) { let i = 0; while (i < len(xs)) { half(xs[i])?; i = i + 1; } Ok(i) }; count([2, 0, 4]);",
+            "match (half(0)) { Result.Ok(v) => v, Result.Err(e) => e };",
+            "[Ok(1).value, Err(\"x\").error];",
+            "let x = 5?;",
+            "Ok(3)? + Err(\"top\")?;",
+            "fn first(r) { Ok(r?[0] + 1) }; [first(Ok([7])), first(Err(\"none\"))];",
+            "fn field(r) { Ok(r?.a) }; [field(Ok({\"a\": 1})), field(Err(\"none\"))];",
+            "fn size(r) { Ok(r?.len()) }; let r = Ok([1, 2]); [size(r), r];",
+        ];
+        let expected = [
+            "Result.Ok(2)",
+            "Result.Err(\"zero\")",
+            "[ Result.Ok(2), Result.Err(\"zero\") ]",
+            "[ Result.Ok(3), Result.Err(\"zero\") ]",
+            "Result.Err(\"zero\")",
+            "\"zero\"",
+            "[ 1, \"x\" ]",
+            "error: ? expects a Result, got 5",
+            "Result.Err(\"top\")",
+            "[ Result.Ok(8), Result.Err(\"none\") ]",
+            "[ Result.Ok(1), Result.Err(\"none\") ]",
+            "[ Result.Ok(2), Result.Ok([ 1, 2 ]) ]",
+        ];
+
+        for (i, input) in inputs.iter().enumerate() {
+            let program = Parser::build_ast(input);
+            let result = program.eval_statements(&mut env);
+            assert_eq!(result.to_string(), expected.get(i).unwrap().to_string());
+        }
+    }
+
+    #[test]
+    fn eval_stack_traces() {
+        let mut env = Environment::new();
+        let program = Parser::build_ast(
+            "fn inner(x) { x + missing }; let outer = fn(a, b) { inner(a) * b }; fn top() { [1].map(fn(v) { outer(v, 2) }) }; top();",
+        );
+        let result = program.eval_statements(&mut env);
+        let trace = env.take_trace(&result).unwrap();
+
+        assert_eq!(result.to_string(), "error: identifier not found: missing");
+        assert_eq!(
+            trace.to_string(),
+            "    at inner (1:58)\n    at outer (1:101)\n    at <anonymous>\n    at top (1:117)\n"
+        );
+
+        env.set_verbose_traces(true);
+        let result = program.eval_statements(&mut env);
+        let frames = env.take_trace(&result).unwrap().frames;
+
+        assert_eq!(frames[2].to_string(), "outer (1:101) with (1, 2)");
+        assert_eq!(frames[3].to_string(), "inner (1:58) with (1)");
+
+        // a caught error leaves no trace behind
+        let program = Parser::build_ast("try { top(); } catch (e) { e.message }");
+        let result = program.eval_statements(&mut env);
+
+        assert!(env.take_trace(&result).is_none());
+    }
+
+    #[test]
+    fn eval_error_kinds() {
+        let mut env = Environment::new();
+        let inputs = [
+            "try { missing; } catch (e) { e.kind }",
+            "try { 1 + true; } catch (e) { [e.kind, e.message] }",
+            "try { len(1, 2); } catch (e) { [e.kind, e.message] }",
+            "try { len(5); } catch (e) { e.message }",
+            "try { [1].nope; } catch (e) { [e.kind, e.message] }",
+            "try { 5 / (2 - 2); } catch (e) { e.kind }",
+            "try { match (3) { 1 => 1 } } catch (e) { e.kind }",
+            "try { throw 1; } catch (e) { [e.kind, e.cause] }",
+        ];
+        let expected = [
+            "\"NameError\"",
+            "[ \"TypeError\", \"Can only perform operation + on numbers, got: 1 and true \" ]",
+            "[ \"ArityError\", \"'len' expects 1 argument, got 2\" ]",
+            "\"'len' expects a string, array or range, got number\"",
+            "[ \"TypeError\", \"Cannot read nope of array\" ]",
+            "\"DivisionByZero\"",
+            "\"MatchError\"",
+            "[ \"Error\", None ]",
+        ];
+
+        for (i, input) in inputs.iter().enumerate() {
+            let program = Parser::build_ast(input);
+            let result = program.eval_statements(&mut env);
+            assert_eq!(result.to_string(), expected.get(i).unwrap().to_string());
+        }
+
+        let program = Parser::build_ast("fn f(x) { x.nope }; f(2);");
+
+        match program.eval_statements(&mut env) {
+            super::object::Object::Error(err) => {
+                assert_eq!(err.kind, super::error::ErrorKind::TypeError);
+                assert_eq!(err.span.unwrap().to_string(), "1:22");
+            }
+            other => panic!("expected an error, got {}", other),
+        }
+    }
+
+    #[test]
+    fn eval_suggestions() {
+        let mut env = Environment::new();
+        let inputs = [
+            "let counter = 1; conter + 1;",
+            "fisrt([1, 2]);",
+            "countr = 2;",
+            "struct User { name, email }; User(\"a\", \"b\").emial;",
+            "[1, 2].lenn();",
+            "let config = {\"port\": 80}; config.prot();",
+            "enum Shape { Circle, Square }; Shape.Cirlce;",
+            "banana;",
+        ];
+        let expected = [
+            "error: identifier not found: conter, did you mean counter?",
+            "error: identifier not found: fisrt, did you mean first?",
+            "error: identifier not found: countr, did you mean counter?",
+            "error: User has no field emial, did you mean email?",
+            "error: Cannot read lenn of array, did you mean len?",
+            "error: hashmap has no entry or method prot, did you mean port?",
+            "error: Shape has no variant Cirlce, did you mean Circle?",
+            "error: identifier not found: banana",
+        ];
+
+        for (i, input) in inputs.iter().enumerate() {
+            let program = Parser::build_ast(input);
+            let result = program.eval_statements(&mut env);
+            assert_eq!(result.to_string(), expected.get(i).unwrap().to_string());
+        }
+    }
 }
@@ -0,0 +1,226 @@
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+    rc::Rc,
+};
+
+use crate::ast::parser::Parser;
+
+use super::{
+    env::Environment,
+    error::{ErrorKind, RuntimeError},
+    object::Object,
+};
+
+/// Modules loaded during a run, shared by every environment.
+#[derive(Debug, Default)]
+pub struct Modules {
+    cache: HashMap<PathBuf, Object>,
+    /// Files being evaluated, from the entry file down to the current import,
+    /// with the path they were imported as.
+    chain: Vec<(PathBuf, String)>,
+}
+
+impl Environment {
+    /// A global environment to run the file at `path`, its imports resolve relative to it.
+    pub fn for_file(path: &str) -> Self {
+        let mut env = Environment::new();
+        let file = fs::canonicalize(path).unwrap_or_else(|_| PathBuf::from(path));
+
+        env.modules
+            .borrow_mut()
+            .chain
+            .push((file.clone(), path.to_string()));
+        env.file = Some(Rc::new(file));
+
+        env
+    }
+}
+
+/// Evaluates the module at `path` the first time it is imported, later imports
+/// share the cached module object.
+pub fn import_module(path: &str, env: &Environment) -> Result<Object, Object> {
+    let base = match &env.file {
+        Some(file) => file.parent().map(Path::to_path_buf).unwrap_or_default(),
+        None => PathBuf::new(),
+    };
+
+    let file = fs::canonicalize(base.join(path)).map_err(|e| {
+        Object::error(
+            ErrorKind::ImportError,
+            format!("Cannot import {}: {}", path, e),
+        )
+    })?;
+
+    if let Some(module) = env.modules.borrow().cache.get(&file) {
+        return Ok(module.clone());
+    }
+
+    if env.modules.borrow().chain.iter().any(|(f, _)| *f == file) {
+        let chain = env
+            .modules
+            .borrow()
+            .chain
+            .iter()
+            .map(|(_, name)| name.as_str())
+            .chain([path])
+            .collect::<Vec<_>>()
+            .join(" -> ");
+
+        return Err(Object::error(
+            ErrorKind::ImportError,
+            format!("Cyclic import: {}", chain),
+        ));
+    }
+
+    let input = fs::read_to_string(&file).map_err(|e| {
+        Object::error(
+            ErrorKind::ImportError,
+            format!("Cannot import {}: {}", path, e),
+        )
+    })?;
+    let program = Parser::build_ast(&input);
+
+    let mut module_env = Environment::new();
+    module_env.modules = Rc::clone(&env.modules);
+    module_env.calls = Rc::clone(&env.calls);
+    module_env.file = Some(Rc::new(file.clone()));
+
+    if let Some(error) = program.resolve(&module_env).into_iter().next() {
+        let mut wrapped =
+            RuntimeError::new(ErrorKind::ImportError, format!("Cannot import {}", path));
+        wrapped.cause = Some(Box::new(error));
+
+        return Err(Object::Error(Box::new(wrapped)));
+    }
+
+    env.modules
+        .borrow_mut()
+        .chain
+        .push((file.clone(), path.to_string()));
+    let result = program.eval_statements(&mut module_env);
+    env.modules.borrow_mut().chain.pop();
+
+    match result {
+        // Failures of a nested import already name the file they happened in.
+        Object::Error(err) if err.kind != ErrorKind::ImportError => {
+            let mut wrapped =
+                RuntimeError::new(ErrorKind::ImportError, format!("Cannot import {}", path));
+            wrapped.cause = Some(err);
+
+            return Err(Object::Error(Box::new(wrapped)));
+        }
+        err if err.is_error() => return Err(err),
+        _ => {}
+    }
+
+    let exports = program
+        .exports()
+        .into_iter()
+        .filter_map(|name| module_env.get(name.clone()).map(|v| (name, v)))
+        .collect();
+
+    let module = Object::Module {
+        name: path.to_string(),
+        exports,
+    };
+    env.modules.borrow_mut().cache.insert(file, module.clone());
+
+    Ok(module)
+}
+
+#[cfg(test)]
+mod test {
+    use std::{fs, path::PathBuf};
+
+    use crate::{ast::parser::Parser, eval::env::Environment};
+
+    fn write_files(dir: &str, files: &[(&str, &str)]) -> PathBuf {
+        let root = std::env::temp_dir().join(format!("dang-{}-{}", dir, std::process::id()));
+
+        for (name, content) in files {
+            let path = root.join(name);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, content).unwrap();
+        }
+
+        root
+    }
+
+    #[test]
+    fn import_modules() {
+        let root = write_files(
+            "modules",
+            &[
+                (
+                    "lib/math.dang",
+                    "export fn add(x, y) { x + y } export const PI = 3; let hidden = 1; export struct Point { x, y }",
+                ),
+                (
+                    "lib/helpers.dang",
+                    "import \"math.dang\" as m; export fn double(x) { m.add(x, x) }",
+                ),
+                ("lib/broken.dang", "export const X = 1 + true;"),
+                ("lib/typo.dang", "export fn rarely() { mising }"),
+            ],
+        );
+        let main = root.join("main.dang");
+        let mut env = Environment::for_file(main.to_str().unwrap());
+
+        let inputs = [
+            "import \"lib/math.dang\" as math; math.add(1, 2) + math.PI;",
+            "math.hidden;",
+            "math.Point(1, 2).x;",
+            "import \"lib/helpers.dang\" as helpers; helpers.double(4);",
+            "math;",
+            "import \"lib/nope.dang\" as nope;",
+            "try { import \"lib/broken.dang\" as broken; } catch (e) { [e.kind, e.cause.kind] }",
+            "try { import \"lib/typo.dang\" as typo; } catch (e) { e.cause.message }",
+        ];
+        let expected = [
+            "6",
+            "error: Module lib/math.dang has no export hidden",
+            "1",
+            "8",
+            "module \"lib/math.dang\"",
+            "error: Cannot import lib/nope.dang: No such file or directory (os error 2)",
+            "[ \"ImportError\", \"TypeError\" ]",
+            "\"identifier not found: mising\"",
+        ];
+
+        for (i, input) in inputs.iter().enumerate() {
+            let program = Parser::build_ast(input);
+            let result = program.eval_statements(&mut env);
+            assert_eq!(result.to_string(), expected.get(i).unwrap().to_string());
+        }
+
+        // math.dang is shared by the entry file and helpers.dang
+        assert_eq!(env.modules.borrow().cache.len(), 2);
+    }
+
+    #[test]
+    fn cyclic_imports() {
+        let root = write_files(
+            "cycles",
+            &[
+                ("cycle/a.dang", "import \"b.dang\" as b;"),
+                ("cycle/b.dang", "import \"a.dang\" as a;"),
+            ],
+        );
+        let main = root.join("main.dang");
+        let main = main.to_str().unwrap();
+        let mut env = Environment::for_file(main);
+
+        let program = Parser::build_ast("import \"cycle/a.dang\" as a;");
+        let result = program.eval_statements(&mut env);
+
+        assert_eq!(
+            result.to_string(),
+            format!(
+                "error: Cyclic import: {} -> cycle/a.dang -> b.dang -> a.dang",
+                main
+            )
+        );
+    }
+}
@@ -1,9 +1,29 @@
-use std::{collections::HashMap, fmt::Display};
+use std::{
+    cell::RefCell,
+    cmp::Ordering,
+    collections::HashMap,
+    fmt::Display,
+    hash::{Hash, Hasher},
+    rc::Rc,
+};
 
-use crate::ast::statement::{Block, Identifier};
+use crate::{
+    ast::{
+        expression::{Expression, Parameter},
+        statement::{display_variants, Block, Identifier},
+    },
+    lex::token::Span,
+};
+
+use super::{
+    env::Environment,
+    error::{ErrorKind, RuntimeError},
+};
 
 type BuiltinFunction = fn(Vec<Object>) -> Object;
 type Elements = Vec<Object>;
+/// The functions of an `impl` block, shared by a type and all of its values.
+pub type Methods = Rc<RefCell<HashMap<Identifier, Object>>>;
 
 #[derive(Debug, Clone)]
 pub enum Object {
@@ -12,11 +32,12 @@ pub enum Object {
     String(String),
     Boolean(bool),
     Return(Box<Object>),
-    Error(String),
+    Error(Box<RuntimeError>),
     Function {
         name: Option<Identifier>,
-        parameters: Vec<Identifier>,
+        parameters: Vec<Parameter>,
         body: Block,
+        env: Environment,
     },
     Array(Elements),
     Builtin {
@@ -25,22 +46,330 @@ pub enum Object {
     HashMap {
         pairs: HashMap<HashKey, Object>,
     },
+    Range(Range),
+    /// A struct declaration, calling it builds a `Struct`.
+    StructType {
+        name: Identifier,
+        fields: Vec<Identifier>,
+        methods: Methods,
+    },
+    /// A struct value, its fields keep the order of the declaration.
+    Struct {
+        name: Identifier,
+        fields: Vec<(Identifier, Object)>,
+        methods: Methods,
+    },
+    /// An enum declaration, its variants are reached with `Shape.Circle`.
+    EnumType {
+        name: Identifier,
+        variants: Vec<(Identifier, Vec<Identifier>)>,
+        methods: Methods,
+    },
+    /// The constructor of an enum variant that carries a payload.
+    EnumVariant {
+        name: Identifier,
+        variant: Identifier,
+        fields: Vec<Identifier>,
+        methods: Methods,
+    },
+    /// An enum value, the tag of its variant and its payload.
+    Enum {
+        name: Identifier,
+        variant: Identifier,
+        values: Vec<(Identifier, Object)>,
+        methods: Methods,
+    },
+    /// An imported file, its exports are read with `math.add`.
+    Module {
+        name: String,
+        exports: HashMap<Identifier, Object>,
+    },
+    /// An array or hashmap made immutable by `freeze`, its nested collections are frozen too.
+    Frozen(Box<Object>),
+    /// A value raised by `throw`, always an `Error` struct made by `error_value`.
+    Thrown(Box<Object>),
+    /// A call left pending by `return f(...)`, the caller runs it once the current frame is gone.
+    TailCall(Box<TailCall>),
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+/// The callee and evaluated arguments of a call in tail position.
+#[derive(Debug, Clone)]
+pub struct TailCall {
+    pub func: Object,
+    pub function: Expression,
+    pub positional: Vec<Object>,
+    pub named: Vec<(Identifier, Object)>,
+    pub span: Span,
+}
+
+impl Object {
+    /// Deeply freezes arrays, hashmaps and structs, other values are returned as is.
+    pub fn freeze(self) -> Object {
+        match self {
+            Object::Array(arr) => Object::Frozen(Box::new(Object::Array(
+                arr.into_iter().map(Object::freeze).collect(),
+            ))),
+            Object::HashMap { pairs } => Object::Frozen(Box::new(Object::HashMap {
+                pairs: pairs.into_iter().map(|(k, v)| (k, v.freeze())).collect(),
+            })),
+            Object::Struct {
+                name,
+                fields,
+                methods,
+            } => Object::Frozen(Box::new(Object::Struct {
+                name,
+                fields: fields.into_iter().map(|(k, v)| (k, v.freeze())).collect(),
+                methods,
+            })),
+            obj => obj,
+        }
+    }
+
+    /// Looks through `Frozen`, reading a frozen value works like reading the value itself.
+    pub fn thawed(&self) -> &Object {
+        match self {
+            Object::Frozen(inner) => inner,
+            obj => obj,
+        }
+    }
+
+    pub fn thaw(self) -> Object {
+        match self {
+            Object::Frozen(inner) => *inner,
+            obj => obj,
+        }
+    }
+
+    pub fn error(kind: ErrorKind, message: String) -> Object {
+        Object::Error(Box::new(RuntimeError::new(kind, message)))
+    }
+
+    /// The name of the value's type as scripts know it, for error messages.
+    pub fn type_name(&self) -> String {
+        match self {
+            Object::None => String::from("none"),
+            Object::Number(_) => String::from("number"),
+            Object::String(_) => String::from("string"),
+            Object::Boolean(_) => String::from("boolean"),
+            Object::Return(r) => r.type_name(),
+            Object::Error(_) | Object::Thrown(_) => String::from("error"),
+            Object::Function { .. } | Object::Builtin { .. } | Object::TailCall(_) => {
+                String::from("function")
+            }
+            Object::Array(_) => String::from("array"),
+            Object::HashMap { .. } => String::from("hashmap"),
+            Object::Range(_) => String::from("range"),
+            Object::StructType { name, .. } => format!("struct {}", name),
+            Object::EnumType { name, .. } => format!("enum {}", name),
+            Object::EnumVariant { name, variant, .. } => format!("{}.{}", name, variant),
+            Object::Struct { name, .. } | Object::Enum { name, .. } => name.clone(),
+            Object::Module { .. } => String::from("module"),
+            Object::Frozen(inner) => inner.type_name(),
+        }
+    }
+
+    /// Runtime errors and thrown values, both unwind until a `catch` handles them.
+    pub fn is_error(&self) -> bool {
+        matches!(self, Object::Error(_) | Object::Thrown(_))
+    }
+
+    /// Errors and the early `return` of `?`, both stop the expression being evaluated.
+    pub fn is_abrupt(&self) -> bool {
+        self.is_error() || matches!(self, Object::Return(_))
+    }
+
+    /// The value a `catch` binds for this error.
+    pub fn into_caught(self) -> Object {
+        match self {
+            Object::Thrown(err) => *err,
+            Object::Error(err) => caught_error(*err),
+            obj => obj,
+        }
+    }
+}
+
+/// Builds the `Error { kind, message, value, cause }` struct seen by `catch`, `value`
+/// is whatever was thrown.
+pub fn error_value(kind: &str, message: String, value: Object, cause: Object) -> Object {
+    Object::Struct {
+        name: String::from("Error"),
+        fields: vec![
+            (String::from("kind"), Object::String(kind.to_string())),
+            (String::from("message"), Object::String(message)),
+            (String::from("value"), value),
+            (String::from("cause"), cause),
+        ],
+        methods: Methods::default(),
+    }
+}
+
+fn caught_error(err: RuntimeError) -> Object {
+    let cause = match err.cause {
+        Some(cause) => caught_error(*cause),
+        None => Object::None,
+    };
+
+    error_value(&err.kind.to_string(), err.message, Object::None, cause)
+}
+
+/// A lazy sequence of numbers, `start..end step n` or `start..=end step n`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Range {
+    pub start: i64,
+    pub end: i64,
+    pub step: i64,
+    pub inclusive: bool,
+}
+
+// Bounds are widened to i128, ranges spanning most of i64 would overflow otherwise
+impl Range {
+    /// The number of items, saturated at `i64::MAX` for ranges like `i64::MIN..=i64::MAX`.
+    pub fn len(&self) -> i64 {
+        let (start, end, step) = (self.start as i128, self.end as i128, self.step as i128);
+        let (distance, step) = match step > 0 {
+            true => (end - start, step),
+            false => (start - end, -step),
+        };
+
+        let len = match (self.inclusive, distance) {
+            (true, d) if d >= 0 => d / step + 1,
+            (false, d) if d > 0 => (d - 1) / step + 1,
+            _ => 0,
+        };
+
+        len.min(i64::MAX as i128) as i64
+    }
+
+    pub fn get(&self, idx: i64) -> Option<i64> {
+        if idx < 0 || idx >= self.len() {
+            return None;
+        }
+
+        Some(self.at(idx))
+    }
+
+    pub fn contains(&self, n: i64) -> bool {
+        let offset = n as i128 - self.start as i128;
+        let step = self.step as i128;
+
+        offset % step == 0 && i64::try_from(offset / step).is_ok_and(|idx| self.get(idx).is_some())
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = i64> {
+        let range = *self;
+
+        (0..range.len()).map(move |idx| range.at(idx))
+    }
+
+    /// The items in `0..len`, like the indexes of an array it slices. Items before
+    /// them are skipped without being walked.
+    pub fn iter_within(&self, len: i64) -> impl Iterator<Item = i64> {
+        let range = *self;
+        let step = (self.step as i128).abs();
+        let before = match self.step > 0 {
+            true => -(self.start as i128),
+            false => self.start as i128 - (len as i128 - 1),
+        };
+        let skip = match before > 0 {
+            true => ((before + step - 1) / step).min(i64::MAX as i128) as i64,
+            false => 0,
+        };
+
+        (skip..range.len())
+            .map(move |idx| range.at(idx))
+            .take_while(move |i| (0..len).contains(i))
+    }
+
+    // only called with indexes below `len`, so the item fits in i64
+    fn at(&self, idx: i64) -> i64 {
+        (self.start as i128 + idx as i128 * self.step as i128) as i64
+    }
+}
+
+impl Display for Range {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let op = if self.inclusive { "..=" } else { ".." };
+
+        match self.step {
+            1 => write!(f, "{}{}{}", self.start, op, self.end),
+            _ => write!(f, "{}{}{} step {}", self.start, op, self.end, self.step),
+        }
+    }
+}
+
+/// A hashmap key. Enum keys keep their value, so they never collide with a string
+/// of the same text and `keys()` gives them back as enums.
+#[derive(Debug, Clone)]
 pub struct HashKey {
     value: String,
+    variant: Option<Box<Object>>,
 }
 
 impl HashKey {
     pub fn new(s: String) -> Self {
-        Self { value: s }
+        Self {
+            value: s,
+            variant: None,
+        }
+    }
+
+    fn variant(obj: &Object) -> Self {
+        Self {
+            value: obj.to_string(),
+            variant: Some(Box::new(obj.clone())),
+        }
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.value
+    }
+
+    /// Keys other than enums only keep their text, so `keys()` gives them back as strings.
+    pub fn to_object(&self) -> Object {
+        match &self.variant {
+            Some(variant) => variant.as_ref().clone(),
+            None => Object::String(self.value.clone()),
+        }
+    }
+
+    // keys hash and compare by this alone, the cells inside a kept enum never count
+    fn identity(&self) -> (bool, &str) {
+        (self.variant.is_some(), &self.value)
+    }
+}
+
+impl PartialEq for HashKey {
+    fn eq(&self, other: &Self) -> bool {
+        self.identity() == other.identity()
+    }
+}
+
+impl Eq for HashKey {}
+
+impl Hash for HashKey {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.identity().hash(state)
+    }
+}
+
+impl PartialOrd for HashKey {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for HashKey {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.identity().cmp(&other.identity())
     }
 }
 
 impl Display for HashKey {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{:?}", self.value)
+        match &self.variant {
+            Some(variant) => write!(f, "{}", variant),
+            None => write!(f, "{:?}", self.value),
+        }
     }
 }
 
@@ -58,6 +387,9 @@ impl CustomHash for Object {
             })),
             Object::Number(n) => Some(HashKey::new(format!("{}", n))),
             Object::String(s) => Some(HashKey::new(s.clone())),
+            Object::Enum { values, .. } if values.iter().all(|(_, v)| v.hash().is_some()) => {
+                Some(HashKey::variant(self))
+            }
             _ => None,
         }
     }
@@ -71,11 +403,22 @@ impl Display for Object {
             Object::String(s) => write!(f, "\"{}\"", s),
             Object::Boolean(b) => write!(f, "{}", b),
             Object::Return(r) => write!(f, "{}", r),
-            Object::Error(s) => write!(f, "error: {}", s),
+            Object::Thrown(err) => match err.as_ref() {
+                Object::Struct { fields, .. } => {
+                    match fields.iter().find(|(k, _)| k == "message") {
+                        Some((_, Object::String(message))) => write!(f, "error: {}", message),
+                        _ => write!(f, "error: {}", err),
+                    }
+                }
+                err => write!(f, "error: {}", err),
+            },
+            Object::TailCall(call) => write!(f, "TailCall {}", call.function),
+            Object::Error(err) => write!(f, "error: {}", err),
             Object::Function {
                 name,
                 parameters,
                 body,
+                ..
             } => match name {
                 Some(n) => write!(
                     f,
@@ -124,13 +467,57 @@ impl Display for Object {
                     .collect::<Vec<_>>()
                     .join(", ")
             ),
+            Object::Range(range) => write!(f, "{}", range),
+            Object::StructType { name, fields, .. } => {
+                write!(f, "struct {} {{ {} }}", name, fields.join(", "))
+            }
+            Object::Struct { name, fields, .. } => write!(
+                f,
+                "{} {{ {} }}",
+                name,
+                fields
+                    .iter()
+                    .map(|(k, v)| format!("{}: {}", k, v))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+            Object::EnumType { name, variants, .. } => {
+                write!(f, "enum {} {{ {} }}", name, display_variants(variants))
+            }
+            Object::EnumVariant {
+                name,
+                variant,
+                fields,
+                ..
+            } => write!(f, "{}.{}({})", name, variant, fields.join(", ")),
+            Object::Enum {
+                name,
+                variant,
+                values,
+                ..
+            } => match values.is_empty() {
+                true => write!(f, "{}.{}", name, variant),
+                false => write!(
+                    f,
+                    "{}.{}({})",
+                    name,
+                    variant,
+                    values
+                        .iter()
+                        .map(|(_, v)| v.to_string())
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                ),
+            },
+            Object::Module { name, .. } => write!(f, "module \"{}\"", name),
+            Object::Frozen(inner) => write!(f, "{}", inner),
         }
     }
 }
 
 #[cfg(test)]
 mod test {
-    use super::Object;
+    use super::{Object, Range};
     use crate::eval::object::CustomHash;
 
     #[test]
@@ -151,6 +538,32 @@ mod test {
         );
     }
 
+    #[test]
+    fn test_range() {
+        let exclusive = Range {
+            start: 0,
+            end: 10,
+            step: 3,
+            inclusive: false,
+        };
+        let inclusive = Range {
+            start: 10,
+            end: 0,
+            step: -5,
+            inclusive: true,
+        };
+
+        assert_eq!(exclusive.len(), 4);
+        assert_eq!(exclusive.iter().collect::<Vec<_>>(), vec![0, 3, 6, 9]);
+        assert!(exclusive.contains(9));
+        assert!(!exclusive.contains(10));
+        assert_eq!(exclusive.get(4), None);
+
+        assert_eq!(inclusive.iter().collect::<Vec<_>>(), vec![10, 5, 0]);
+        assert!(inclusive.contains(0));
+        assert!(!inclusive.contains(-5));
+    }
+
     #[test]
     fn test_number_hash() {
         let hello_one = Object::Number(-1);
@@ -1,22 +1,66 @@
-use crate::ast::statement::Statement;
+use crate::ast::{
+    expression::run_tail_call,
+    resolver::Resolver,
+    statement::{Identifier, Statement},
+};
 
-use super::{env::Environment, object::Object};
+use super::{
+    env::Environment,
+    error::{ErrorKind, RuntimeError},
+    hoist_functions,
+    object::Object,
+};
 
 pub struct Program {
     pub statements: Vec<Statement>,
+    /// The syntax error parsing stopped at, the statements before it are kept.
+    pub error: Option<String>,
 }
 
 impl Program {
     pub fn eval_statements(&self, env: &mut Environment) -> Object {
+        if let Some(e) = &self.error {
+            return Object::error(ErrorKind::SyntaxError, e.clone());
+        }
+
         let mut result = Object::None;
+
+        hoist_functions(&self.statements, env);
+
         for stmt in self.statements.iter() {
             result = stmt.eval(env);
 
-            if let Object::Return(_) = result {
+            if let Object::Return(r) = result {
+                return Object::Return(Box::new(run_tail_call(*r)));
+            }
+
+            if result.is_error() {
                 break;
             }
         }
 
         result
     }
+
+    /// Finds the mistakes that can be told without running the program, names bound
+    /// in `env` count as declared. A syntax error is all there is to report, names
+    /// around it are likely wrong because of it.
+    pub fn resolve(&self, env: &Environment) -> Vec<RuntimeError> {
+        if let Some(e) = &self.error {
+            return vec![RuntimeError::new(ErrorKind::SyntaxError, e.clone())];
+        }
+
+        Resolver::resolve(&self.statements, env.names())
+    }
+
+    /// Names declared by the top level `export` statements.
+    pub fn exports(&self) -> Vec<Identifier> {
+        self.statements
+            .iter()
+            .filter_map(|sttm| match sttm {
+                Statement::Export(declaration) => declaration.declared_name().cloned(),
+                _ => None,
+            })
+            .collect()
+    }
 }
@@ -1,10 +1,18 @@
 use crate::lex::token::TokenType;
 
-use super::token::Token;
+use super::token::{Span, Token};
+
+/// Words the lexer turns into their own tokens instead of identifiers.
+pub const KEYWORDS: &[&str] = &[
+    "let", "const", "struct", "enum", "impl", "import", "export", "as", "fn", "true", "false",
+    "return", "if", "else", "while", "match", "for", "in", "try", "catch", "finally", "throw",
+];
 
 pub struct Lexer {
     input: String,
     position: usize,
+    line: usize,
+    column: usize,
 }
 
 impl Lexer {
@@ -12,10 +20,35 @@ impl Lexer {
         Self {
             input: input.to_string(),
             position: 0,
+            line: 1,
+            column: 1,
         }
     }
 
+    /// Position to come back to after looking ahead, see `Lexer::restore`.
+    pub fn checkpoint(&self) -> (usize, usize, usize) {
+        (self.position, self.line, self.column)
+    }
+
+    pub fn restore(&mut self, (position, line, column): (usize, usize, usize)) {
+        self.position = position;
+        self.line = line;
+        self.column = column;
+    }
+
     pub fn next_token(&mut self) -> Option<Token> {
+        let span = Span {
+            line: self.line,
+            column: self.column,
+        };
+
+        self.read_token().map(|mut t| {
+            t.span = span;
+            t
+        })
+    }
+
+    fn read_token(&mut self) -> Option<Token> {
         if self.input.len() < self.position {
             return None;
         }
@@ -30,10 +63,17 @@ impl Lexer {
             return Some(Token::whitespace());
         }
 
-        if curr.is_alphabetic() {
+        if curr.is_alphabetic() || curr == '_' {
             let word = self.consume_word(curr);
             match word.as_str() {
                 "let" => return Some(Token::new_let()),
+                "const" => return Some(Token::new(TokenType::Const, word)),
+                "struct" => return Some(Token::new(TokenType::Struct, word)),
+                "enum" => return Some(Token::new(TokenType::Enum, word)),
+                "impl" => return Some(Token::new(TokenType::Impl, word)),
+                "import" => return Some(Token::new(TokenType::Import, word)),
+                "export" => return Some(Token::new(TokenType::Export, word)),
+                "as" => return Some(Token::new(TokenType::As, word)),
                 "fn" => return Some(Token::function()),
                 "true" => return Some(Token::boolean(true)),
                 "false" => return Some(Token::boolean(false)),
@@ -41,6 +81,13 @@ impl Lexer {
                 "if" => return Some(Token::new(TokenType::If, word)),
                 "else" => return Some(Token::new(TokenType::Else, word)),
                 "while" => return Some(Token::while_token()),
+                "match" => return Some(Token::new(TokenType::Match, word)),
+                "for" => return Some(Token::new(TokenType::For, word)),
+                "in" => return Some(Token::new(TokenType::In, word)),
+                "try" => return Some(Token::new(TokenType::Try, word)),
+                "catch" => return Some(Token::new(TokenType::Catch, word)),
+                "finally" => return Some(Token::new(TokenType::Finally, word)),
+                "throw" => return Some(Token::new(TokenType::Throw, word)),
                 _ => return Some(Token::identifier(word)),
             }
         }
@@ -56,6 +103,10 @@ impl Lexer {
                     self.consume_char();
                     Some(Token::new(TokenType::Eq, "==".to_string()))
                 }
+                Some('>') => {
+                    self.consume_char();
+                    Some(Token::new(TokenType::FatArrow, "=>".to_string()))
+                }
                 _ => Some(Token::assign_sign()),
             },
             '!' => match self.peek() {
@@ -65,12 +116,55 @@ impl Lexer {
                 }
                 _ => Some(Token::bang()),
             },
-            '.' => Some(Token::dot()),
+            '.' => match (self.peek(), self.peek_second()) {
+                (Some('.'), Some('.')) => {
+                    self.consume_char();
+                    self.consume_char();
+                    Some(Token::ellipsis())
+                }
+                (Some('.'), Some('=')) => {
+                    self.consume_char();
+                    self.consume_char();
+                    Some(Token::new(TokenType::DotDotEq, "..=".to_string()))
+                }
+                (Some('.'), _) => {
+                    self.consume_char();
+                    Some(Token::new(TokenType::DotDot, "..".to_string()))
+                }
+                _ => Some(Token::dot()),
+            },
             ';' => Some(Token::semicolon()),
-            '+' => Some(Token::new(TokenType::PlusSign, curr.to_string())),
-            '-' => Some(Token::new(TokenType::MinusSign, curr.to_string())),
-            '*' => Some(Token::new(TokenType::MultiplicationSign, curr.to_string())),
-            '/' => Some(Token::new(TokenType::SlashSign, curr.to_string())),
+            '+' => match self.peek() {
+                Some('=') => {
+                    self.consume_char();
+                    Some(Token::new(TokenType::PlusAssign, "+=".to_string()))
+                }
+                _ => Some(Token::new(TokenType::PlusSign, curr.to_string())),
+            },
+            '-' => match self.peek() {
+                Some('=') => {
+                    self.consume_char();
+                    Some(Token::new(TokenType::MinusAssign, "-=".to_string()))
+                }
+                _ => Some(Token::new(TokenType::MinusSign, curr.to_string())),
+            },
+            '*' => match self.peek() {
+                Some('=') => {
+                    self.consume_char();
+                    Some(Token::new(
+                        TokenType::MultiplicationAssign,
+                        "*=".to_string(),
+                    ))
+                }
+                _ => Some(Token::new(TokenType::MultiplicationSign, curr.to_string())),
+            },
+            '/' => match self.peek() {
+                Some('=') => {
+                    self.consume_char();
+                    Some(Token::new(TokenType::SlashAssign, "/=".to_string()))
+                }
+                _ => Some(Token::new(TokenType::SlashSign, curr.to_string())),
+            },
             '{' => Some(Token::left_brace()),
             '}' => Some(Token::right_brace()),
             '(' => Some(Token::left_paren()),
@@ -92,6 +186,22 @@ impl Lexer {
                 }
                 _ => Some(Token::gt()),
             },
+            // `r?.x` and `r?[0]` lex as optional chaining, which unwraps a Result like `r?`
+            '?' => match self.peek() {
+                Some('?') => {
+                    self.consume_char();
+                    Some(Token::new(TokenType::NullCoalescing, "??".to_string()))
+                }
+                Some('.') => {
+                    self.consume_char();
+                    Some(Token::new(TokenType::OptionalDot, "?.".to_string()))
+                }
+                Some('[') => {
+                    self.consume_char();
+                    Some(Token::new(TokenType::OptionalBracket, "?[".to_string()))
+                }
+                _ => Some(Token::new(TokenType::Question, "?".to_string())),
+            },
             '"' => Some(Token::string(self.consume_string())),
             ':' => Some(Token::colon()),
             '&' => match self.peek() {
@@ -107,8 +217,7 @@ impl Lexer {
                     self.consume_char();
                     Some(Token::or())
                 }
-                // Bitwise operation
-                _ => Some(Token::illegal()),
+                _ => Some(Token::new(TokenType::Pipe, curr.to_string())),
             },
             _ => Some(Token::illegal()),
         }
@@ -123,6 +232,13 @@ impl Lexer {
 
         self.position += 1;
 
+        if c == '\n' {
+            self.line += 1;
+            self.column = 1;
+        } else {
+            self.column += 1;
+        }
+
         c
     }
 
@@ -130,6 +246,10 @@ impl Lexer {
         self.input.chars().nth(self.position)
     }
 
+    fn peek_second(&self) -> Option<char> {
+        self.input.chars().nth(self.position + 1)
+    }
+
     fn consume_word(&mut self, mut initial_char: char) -> String {
         let mut word = String::from("");
 
@@ -138,7 +258,7 @@ impl Lexer {
 
             match self.peek() {
                 Some(d) => {
-                    if !d.is_alphanumeric() {
+                    if !d.is_alphanumeric() && d != '_' {
                         break;
                     }
                 }
@@ -221,7 +341,7 @@ mod test {
             }
         }
 
-        return tokens;
+        tokens
     }
 
     #[test]
@@ -615,4 +735,304 @@ mod test {
 
         assert_eq!(expected, result)
     }
+
+    #[test]
+    fn destructuring() {
+        let input = "
+        let [a, ...rest] = xs;
+        ";
+
+        let lex = Lexer::new(input);
+        let expected: Vec<Token> = vec![
+            Token::new_let(),
+            Token::left_bracket(),
+            Token::identifier("a".into()),
+            Token::comma(),
+            Token::ellipsis(),
+            Token::identifier("rest".into()),
+            Token::right_bracket(),
+            Token::assign_sign(),
+            Token::identifier("xs".into()),
+            Token::semicolon(),
+            Token::eof(),
+        ];
+        let result = run_tokenizer(lex);
+
+        assert_eq!(expected, result)
+    }
+
+    #[test]
+    fn match_expression() {
+        let input = "
+        match (x) { 1 | 2 => a, _ if my_flag => b }
+        ";
+
+        let lex = Lexer::new(input);
+        let expected: Vec<Token> = vec![
+            Token::new(TokenType::Match, "match".into()),
+            Token::left_paren(),
+            Token::identifier("x".into()),
+            Token::right_paren(),
+            Token::left_brace(),
+            Token::int(1),
+            Token::new(TokenType::Pipe, "|".into()),
+            Token::int(2),
+            Token::new(TokenType::FatArrow, "=>".into()),
+            Token::identifier("a".into()),
+            Token::comma(),
+            Token::identifier("_".into()),
+            Token::new(TokenType::If, "if".into()),
+            Token::identifier("my_flag".into()),
+            Token::new(TokenType::FatArrow, "=>".into()),
+            Token::identifier("b".into()),
+            Token::right_brace(),
+            Token::eof(),
+        ];
+        let result = run_tokenizer(lex);
+
+        assert_eq!(expected, result)
+    }
+
+    #[test]
+    fn optional_chaining() {
+        let input = "
+        a?.b?[0] ?? c;
+        ";
+
+        let lex = Lexer::new(input);
+        let expected: Vec<Token> = vec![
+            Token::identifier("a".into()),
+            Token::new(TokenType::OptionalDot, "?.".into()),
+            Token::identifier("b".into()),
+            Token::new(TokenType::OptionalBracket, "?[".into()),
+            Token::int(0),
+            Token::right_bracket(),
+            Token::new(TokenType::NullCoalescing, "??".into()),
+            Token::identifier("c".into()),
+            Token::semicolon(),
+            Token::eof(),
+        ];
+        let result = run_tokenizer(lex);
+
+        assert_eq!(expected, result)
+    }
+
+    #[test]
+    fn ranges() {
+        let input = "
+        for (i in 0..10) { }
+        0..=n step 2;
+        ";
+
+        let lex = Lexer::new(input);
+        let expected: Vec<Token> = vec![
+            Token::new(TokenType::For, "for".into()),
+            Token::left_paren(),
+            Token::identifier("i".into()),
+            Token::new(TokenType::In, "in".into()),
+            Token::int(0),
+            Token::new(TokenType::DotDot, "..".into()),
+            Token::int(10),
+            Token::right_paren(),
+            Token::left_brace(),
+            Token::right_brace(),
+            Token::int(0),
+            Token::new(TokenType::DotDotEq, "..=".into()),
+            Token::identifier("n".into()),
+            Token::identifier("step".into()),
+            Token::int(2),
+            Token::semicolon(),
+            Token::eof(),
+        ];
+        let result = run_tokenizer(lex);
+
+        assert_eq!(expected, result)
+    }
+
+    #[test]
+    fn token_spans() {
+        let input = "let a = 1;\n  a += 2;";
+
+        let mut lex = Lexer::new(input);
+        let mut spans = vec![];
+
+        while let Some(t) = lex.next_token() {
+            if t.kind != TokenType::Whitespace {
+                spans.push((t.literal, t.span.line, t.span.column));
+            }
+        }
+
+        assert_eq!(
+            spans,
+            vec![
+                ("let".to_string(), 1, 1),
+                ("a".to_string(), 1, 5),
+                ("=".to_string(), 1, 7),
+                ("1".to_string(), 1, 9),
+                (";".to_string(), 1, 10),
+                ("a".to_string(), 2, 3),
+                ("+=".to_string(), 2, 5),
+                ("2".to_string(), 2, 8),
+                (";".to_string(), 2, 9),
+                ("\0".to_string(), 2, 10),
+            ]
+        );
+    }
+
+    #[test]
+    fn compound_assignment() {
+        let input = "
+        a += 1;
+        a -= 1;
+        a *= 2;
+        a /= 2;
+        ";
+
+        let lex = Lexer::new(input);
+        let expected: Vec<Token> = vec![
+            Token::identifier("a".into()),
+            Token::new(TokenType::PlusAssign, "+=".into()),
+            Token::int(1),
+            Token::semicolon(),
+            Token::identifier("a".into()),
+            Token::new(TokenType::MinusAssign, "-=".into()),
+            Token::int(1),
+            Token::semicolon(),
+            Token::identifier("a".into()),
+            Token::new(TokenType::MultiplicationAssign, "*=".into()),
+            Token::int(2),
+            Token::semicolon(),
+            Token::identifier("a".into()),
+            Token::new(TokenType::SlashAssign, "/=".into()),
+            Token::int(2),
+            Token::semicolon(),
+            Token::eof(),
+        ];
+        let result = run_tokenizer(lex);
+
+        assert_eq!(expected, result)
+    }
+
+    #[test]
+    fn const_statement() {
+        let input = "
+        const LIMIT = 10;
+        ";
+
+        let lex = Lexer::new(input);
+        let expected: Vec<Token> = vec![
+            Token::new(TokenType::Const, "const".into()),
+            Token::identifier("LIMIT".into()),
+            Token::assign_sign(),
+            Token::int(10),
+            Token::semicolon(),
+            Token::eof(),
+        ];
+        let result = run_tokenizer(lex);
+
+        assert_eq!(expected, result)
+    }
+
+    #[test]
+    fn struct_declaration() {
+        let input = "
+        struct Point { x, y }
+        ";
+
+        let lex = Lexer::new(input);
+        let expected: Vec<Token> = vec![
+            Token::new(TokenType::Struct, "struct".into()),
+            Token::identifier("Point".into()),
+            Token::left_brace(),
+            Token::identifier("x".into()),
+            Token::comma(),
+            Token::identifier("y".into()),
+            Token::right_brace(),
+            Token::eof(),
+        ];
+        let result = run_tokenizer(lex);
+
+        assert_eq!(expected, result)
+    }
+
+    #[test]
+    fn enum_declaration() {
+        let input = "
+        enum Shape { Circle(r), Empty }
+        ";
+
+        let lex = Lexer::new(input);
+        let expected: Vec<Token> = vec![
+            Token::new(TokenType::Enum, "enum".into()),
+            Token::identifier("Shape".into()),
+            Token::left_brace(),
+            Token::identifier("Circle".into()),
+            Token::left_paren(),
+            Token::identifier("r".into()),
+            Token::right_paren(),
+            Token::comma(),
+            Token::identifier("Empty".into()),
+            Token::right_brace(),
+            Token::eof(),
+        ];
+        let result = run_tokenizer(lex);
+
+        assert_eq!(expected, result)
+    }
+
+    #[test]
+    fn try_catch_statement() {
+        let input = "
+        try { throw e; } catch (e) {} finally {}
+        ";
+
+        let lex = Lexer::new(input);
+        let expected: Vec<Token> = vec![
+            Token::new(TokenType::Try, "try".into()),
+            Token::left_brace(),
+            Token::new(TokenType::Throw, "throw".into()),
+            Token::identifier("e".into()),
+            Token::semicolon(),
+            Token::right_brace(),
+            Token::new(TokenType::Catch, "catch".into()),
+            Token::left_paren(),
+            Token::identifier("e".into()),
+            Token::right_paren(),
+            Token::left_brace(),
+            Token::right_brace(),
+            Token::new(TokenType::Finally, "finally".into()),
+            Token::left_brace(),
+            Token::right_brace(),
+            Token::eof(),
+        ];
+        let result = run_tokenizer(lex);
+
+        assert_eq!(expected, result)
+    }
+
+    #[test]
+    fn question_mark() {
+        let input = "
+        parse(x)? ?? xs?[0];
+        ";
+
+        let lex = Lexer::new(input);
+        let expected: Vec<Token> = vec![
+            Token::identifier("parse".into()),
+            Token::left_paren(),
+            Token::identifier("x".into()),
+            Token::right_paren(),
+            Token::new(TokenType::Question, "?".into()),
+            Token::new(TokenType::NullCoalescing, "??".into()),
+            Token::identifier("xs".into()),
+            Token::new(TokenType::OptionalBracket, "?[".into()),
+            Token::int(0),
+            Token::right_bracket(),
+            Token::semicolon(),
+            Token::eof(),
+        ];
+        let result = run_tokenizer(lex);
+
+        assert_eq!(expected, result)
+    }
 }
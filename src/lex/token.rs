@@ -1,9 +1,14 @@
-use std::fmt::Debug;
+use std::fmt::{Debug, Display};
 
 #[derive(Debug, PartialEq, Clone, Eq, Hash)]
 pub enum TokenType {
     Comma,
     Dot,
+    // -> ..
+    DotDot,
+    // -> ..=
+    DotDotEq,
+    Ellipsis,
     Semicolon,
     Colon,
     Eof,
@@ -18,10 +23,24 @@ pub enum TokenType {
 
     // keywords
     Let,
+    Const,
+    Struct,
+    Enum,
+    Impl,
+    Import,
+    Export,
+    As,
     Function,
     If,
     Else,
     Return,
+    For,
+    In,
+    Match,
+    Try,
+    Catch,
+    Finally,
+    Throw,
 
     // literals
     Boolean(bool),
@@ -37,6 +56,14 @@ pub enum TokenType {
     MultiplicationSign,
     SlashSign,
     Asssign,
+    // -> =>
+    FatArrow,
+    // -> |
+    Pipe,
+    PlusAssign,
+    MinusAssign,
+    MultiplicationAssign,
+    SlashAssign,
     // -> !
     BangSign,
     LT,
@@ -47,25 +74,58 @@ pub enum TokenType {
     NotEq,
     And,
     Or,
+    // -> ??
+    NullCoalescing,
+    Question,
+    // -> ?.
+    OptionalDot,
+    // -> ?[
+    OptionalBracket,
 
     While,
 }
 
-#[derive(Debug, PartialEq, Clone)]
+/// Where a token starts in the source, both 1-based.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Span {
+    pub line: usize,
+    pub column: usize,
+}
+
+impl Display for Span {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}:{}", self.line, self.column)
+    }
+}
+
+#[derive(Debug, Clone)]
 pub struct Token {
     pub kind: TokenType,
     pub literal: String,
+    pub span: Span,
+}
+
+// Tokens are compared by content, where they were found does not matter
+impl PartialEq for Token {
+    fn eq(&self, other: &Self) -> bool {
+        self.kind == other.kind && self.literal == other.literal
+    }
 }
 
 impl Token {
     pub fn new(tt: TokenType, literal: String) -> Self {
-        Self { kind: tt, literal }
+        Self {
+            kind: tt,
+            literal,
+            span: Span::default(),
+        }
     }
 
     pub fn new_let() -> Self {
         Self {
             kind: TokenType::Let,
             literal: "let".to_string(),
+            span: Span::default(),
         }
     }
 
@@ -73,6 +133,7 @@ impl Token {
         Self {
             kind: TokenType::Eof,
             literal: "\0".to_string(),
+            span: Span::default(),
         }
     }
 
@@ -80,6 +141,7 @@ impl Token {
         Self {
             kind: TokenType::Whitespace,
             literal: " ".to_string(),
+            span: Span::default(),
         }
     }
 
@@ -87,6 +149,7 @@ impl Token {
         Self {
             kind: TokenType::Asssign,
             literal: "=".to_string(),
+            span: Span::default(),
         }
     }
 
@@ -94,6 +157,7 @@ impl Token {
         Self {
             kind: TokenType::Semicolon,
             literal: ";".to_string(),
+            span: Span::default(),
         }
     }
 
@@ -101,6 +165,7 @@ impl Token {
         Self {
             kind: TokenType::LeftParen,
             literal: "(".to_string(),
+            span: Span::default(),
         }
     }
 
@@ -108,6 +173,7 @@ impl Token {
         Self {
             kind: TokenType::RightParen,
             literal: ")".to_string(),
+            span: Span::default(),
         }
     }
 
@@ -115,6 +181,7 @@ impl Token {
         Self {
             kind: TokenType::LeftBrace,
             literal: "{".to_string(),
+            span: Span::default(),
         }
     }
 
@@ -122,6 +189,7 @@ impl Token {
         Self {
             kind: TokenType::RightBrace,
             literal: "}".to_string(),
+            span: Span::default(),
         }
     }
 
@@ -129,6 +197,7 @@ impl Token {
         Self {
             kind: TokenType::LeftBracket,
             literal: "[".to_string(),
+            span: Span::default(),
         }
     }
 
@@ -136,6 +205,7 @@ impl Token {
         Self {
             kind: TokenType::RightBracket,
             literal: "]".to_string(),
+            span: Span::default(),
         }
     }
 
@@ -143,6 +213,7 @@ impl Token {
         Self {
             kind: TokenType::Function,
             literal: "fn".to_string(),
+            span: Span::default(),
         }
     }
 
@@ -150,6 +221,7 @@ impl Token {
         Self {
             kind: TokenType::Comma,
             literal: ",".to_string(),
+            span: Span::default(),
         }
     }
 
@@ -157,6 +229,7 @@ impl Token {
         Self {
             kind: TokenType::BangSign,
             literal: "!".to_string(),
+            span: Span::default(),
         }
     }
 
@@ -164,6 +237,7 @@ impl Token {
         Self {
             kind: TokenType::LT,
             literal: "<".to_string(),
+            span: Span::default(),
         }
     }
 
@@ -171,6 +245,7 @@ impl Token {
         Self {
             kind: TokenType::GT,
             literal: ">".to_string(),
+            span: Span::default(),
         }
     }
 
@@ -178,6 +253,7 @@ impl Token {
         Self {
             kind: TokenType::Lte,
             literal: "<=".to_string(),
+            span: Span::default(),
         }
     }
 
@@ -185,6 +261,7 @@ impl Token {
         Self {
             kind: TokenType::Gte,
             literal: ">=".to_string(),
+            span: Span::default(),
         }
     }
 
@@ -192,6 +269,7 @@ impl Token {
         Self {
             kind: TokenType::Int(n),
             literal: n.to_string(),
+            span: Span::default(),
         }
     }
 
@@ -199,6 +277,7 @@ impl Token {
         Self {
             kind: TokenType::Identifier,
             literal: name,
+            span: Span::default(),
         }
     }
 
@@ -206,6 +285,7 @@ impl Token {
         Self {
             kind: TokenType::String(string.clone()),
             literal: string,
+            span: Span::default(),
         }
     }
 
@@ -213,6 +293,7 @@ impl Token {
         Self {
             kind: TokenType::Boolean(b),
             literal: b.to_string(),
+            span: Span::default(),
         }
     }
 
@@ -220,6 +301,7 @@ impl Token {
         Self {
             kind: TokenType::Colon,
             literal: ":".to_string(),
+            span: Span::default(),
         }
     }
 
@@ -227,6 +309,7 @@ impl Token {
         Self {
             kind: TokenType::Illegal,
             literal: "illegal".to_string(),
+            span: Span::default(),
         }
     }
 
@@ -234,6 +317,7 @@ impl Token {
         Self {
             kind: TokenType::And,
             literal: "&&".to_string(),
+            span: Span::default(),
         }
     }
 
@@ -241,6 +325,7 @@ impl Token {
         Self {
             kind: TokenType::Or,
             literal: "||".to_string(),
+            span: Span::default(),
         }
     }
 
@@ -248,6 +333,7 @@ impl Token {
         Self {
             kind: TokenType::While,
             literal: "while".into(),
+            span: Span::default(),
         }
     }
 
@@ -255,6 +341,15 @@ impl Token {
         Self {
             kind: TokenType::Dot,
             literal: ".".into(),
+            span: Span::default(),
+        }
+    }
+
+    pub fn ellipsis() -> Self {
+        Self {
+            kind: TokenType::Ellipsis,
+            literal: "...".into(),
+            span: Span::default(),
         }
     }
 }
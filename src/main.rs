@@ -1,15 +1,20 @@
 use std::{
     fs,
     io::{stdin, stdout, Write},
+    thread,
 };
 
 use clap::Parser as Parser_Clap;
 
-use crate::{ast::parser::Parser, eval::env::Environment};
+use crate::{
+    ast::parser::Parser,
+    eval::{env::Environment, error::RuntimeError, object::Object, program::Program},
+};
 
 mod ast;
 mod eval;
 mod lex;
+mod suggest;
 
 fn read_file(file_name: &str) -> String {
     match fs::read_to_string(file_name) {
@@ -18,29 +23,103 @@ fn read_file(file_name: &str) -> String {
     }
 }
 
+// Every dang call takes a few evaluator frames, a big stack lets programs
+// recurse as deep as the recursion limit allows
+const STACK_SIZE: usize = 256 * 1024 * 1024;
+
+// The stack calls may use, the rest is left for builtins and the call that
+// finds the stack full
+const STACK_LIMIT: usize = STACK_SIZE / 2;
+
 #[derive(Debug, Parser_Clap)]
 struct Args {
     #[arg(long)]
     file_name: Option<String>,
+    /// How deep calls may nest before the run fails
+    #[arg(long)]
+    max_depth: Option<usize>,
+    /// Show the arguments of every call in stack traces
+    #[arg(long)]
+    verbose: bool,
 }
 
 fn main() {
     let args = Args::parse();
-    let mut env = Environment::new();
 
+    let runner = thread::Builder::new()
+        .stack_size(STACK_SIZE)
+        .spawn(move || run(args))
+        .expect("Failed to start the interpreter");
+
+    runner.join().expect("The interpreter panicked");
+}
+
+fn print_result(obj: &Object, env: &mut Environment) {
+    println!("{}", obj);
+
+    if let Some(trace) = env.take_trace(obj) {
+        print!("{}", trace);
+    } else if let Object::Error(err) = obj {
+        if let Some(span) = err.span {
+            println!("    at {}", span);
+        }
+    }
+}
+
+/// Runs `program` unless the resolver finds mistakes in it, those are printed instead.
+fn check_and_eval(program: &Program, env: &mut Environment) {
+    let errors = program.resolve(env);
+
+    if errors.is_empty() {
+        let obj = program.eval_statements(env);
+        return print_result(&obj, env);
+    }
+
+    for error in errors {
+        print_error(&error);
+    }
+}
+
+fn print_error(error: &RuntimeError) {
+    println!("error: {}", error);
+
+    if let Some(span) = error.span {
+        println!("    at {}", span);
+    }
+}
+
+fn run(args: Args) {
     match args.file_name {
         Some(file_name) => {
             let input = read_file(&file_name);
+            let mut env = Environment::for_file(&file_name);
+
+            if let Some(depth) = args.max_depth {
+                env.set_max_depth(depth);
+            }
+
+            env.set_stack_limit(STACK_LIMIT);
+
+            env.set_verbose_traces(args.verbose);
 
             let program = Parser::build_ast(&input);
-            let obj = program.eval_statements(&mut env);
 
-            println!("{}", obj);
+            check_and_eval(&program, &mut env);
         }
         None => {
             println!("This is the Dan-Lang programming language!");
             println!("Feel free to type in commands");
 
+            let mut env = Environment::new();
+
+            if let Some(depth) = args.max_depth {
+                env.set_max_depth(depth);
+            }
+
+            env.set_stack_limit(STACK_LIMIT);
+
+            env.set_verbose_traces(args.verbose);
+
             loop {
                 print!(">> ");
 
@@ -50,9 +129,7 @@ fn main() {
 
                 let program = Parser::build_ast(&buffer);
 
-                let obj = program.eval_statements(&mut env);
-
-                println!("{}", obj);
+                check_and_eval(&program, &mut env);
             }
         }
     }
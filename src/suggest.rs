@@ -0,0 +1,99 @@
+const MAX_SUGGESTIONS: usize = 3;
+
+/// The number of single character insertions, deletions, substitutions and swaps
+/// of neighbours that turn `a` into `b`.
+pub fn edit_distance(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+
+    // distances[i][j] is the distance between the first i chars of a and the first j of b
+    let mut distances = vec![vec![0; b.len() + 1]; a.len() + 1];
+
+    for (i, row) in distances.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    for (j, distance) in distances[0].iter_mut().enumerate() {
+        *distance = j;
+    }
+
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let substitution = distances[i - 1][j - 1] + usize::from(a[i - 1] != b[j - 1]);
+            let mut distance = substitution
+                .min(distances[i - 1][j] + 1)
+                .min(distances[i][j - 1] + 1);
+
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                distance = distance.min(distances[i - 2][j - 2] + 1);
+            }
+
+            distances[i][j] = distance;
+        }
+    }
+
+    distances[a.len()][b.len()]
+}
+
+/// The candidates close enough to `name` to be what was meant, closest first.
+pub fn closest<'a>(name: &str, candidates: impl IntoIterator<Item = &'a str>) -> Vec<&'a str> {
+    // Longer names leave room for more typos, but some of the name has to survive
+    let length = name.chars().count();
+    let limit = (length / 3).max(1).min(length.saturating_sub(1));
+
+    let mut close: Vec<(usize, &str)> = candidates
+        .into_iter()
+        .filter(|candidate| *candidate != name)
+        .map(|candidate| (edit_distance(name, candidate), candidate))
+        .filter(|(distance, _)| *distance <= limit)
+        .collect();
+
+    close.sort();
+    close.dedup();
+
+    close
+        .into_iter()
+        .take(MAX_SUGGESTIONS)
+        .map(|(_, candidate)| candidate)
+        .collect()
+}
+
+/// A hint to append to an error message, like ", did you mean len?", empty when
+/// nothing is close.
+pub fn did_you_mean<'a>(name: &str, candidates: impl IntoIterator<Item = &'a str>) -> String {
+    match closest(name, candidates).as_slice() {
+        [] => String::new(),
+        [only] => format!(", did you mean {}?", only),
+        [rest @ .., last] => format!(", did you mean {} or {}?", rest.join(", "), last),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{closest, did_you_mean, edit_distance};
+
+    #[test]
+    fn edit_distances() {
+        assert_eq!(edit_distance("lenght", "length"), 1);
+        assert_eq!(edit_distance("len", "lenght"), 3);
+        assert_eq!(edit_distance("retrun", "return"), 1);
+        assert_eq!(edit_distance("kitten", "sitting"), 3);
+        assert_eq!(edit_distance("", "abc"), 3);
+        assert_eq!(edit_distance("same", "same"), 0);
+    }
+
+    #[test]
+    fn suggestions() {
+        assert_eq!(closest("lenght", ["len", "length", "last"]), ["length"]);
+        assert_eq!(closest("xs", ["x", "ys", "xss", "abc"]), ["x", "xss", "ys"]);
+        assert_eq!(closest("x", ["x", "y", "xs"]), Vec::<&str>::new());
+        assert_eq!(
+            did_you_mean("fisrt", ["first", "last"]),
+            ", did you mean first?"
+        );
+        assert_eq!(
+            did_you_mean("cont", ["count", "const"]),
+            ", did you mean const or count?"
+        );
+        assert_eq!(did_you_mean("banana", ["apple"]), "");
+    }
+}